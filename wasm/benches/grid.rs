@@ -0,0 +1,218 @@
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use wasm::bench::Grid;
+
+const SIZE: usize = 256;
+
+/// The layout `Grid` had before it was flattened, one `Vec` per column, as the
+/// baseline of every sweep.
+struct Nested<T> {
+    grid: Vec<Vec<T>>,
+}
+
+impl<T: Default + Copy> Nested<T> {
+    fn new(width: usize, height: usize) -> Nested<T> {
+        Nested {
+            grid: vec![vec![T::default(); height]; width],
+        }
+    }
+
+    fn has(&self, i: i32, k: i32) -> bool {
+        i >= 0 && i < self.grid.len() as i32 && k >= 0 && k < self.grid[0].len() as i32
+    }
+
+    fn get(&self, i: i32, k: i32) -> T {
+        if !self.has(i, k) {
+            T::default()
+        } else {
+            self.grid[i as usize][k as usize]
+        }
+    }
+
+    fn set(&mut self, i: i32, k: i32, value: T) {
+        if self.has(i, k) {
+            self.grid[i as usize][k as usize] = value;
+        }
+    }
+
+    fn update<F: Fn(T) -> T>(&mut self, i: i32, k: i32, f: F) {
+        if self.has(i, k) {
+            let cell = &mut self.grid[i as usize][k as usize];
+            *cell = f(*cell);
+        }
+    }
+}
+
+fn sweep_get(c: &mut Criterion) {
+    let nested = Nested::<f64>::new(SIZE, SIZE);
+    let grid = Grid::<f64>::new(SIZE, SIZE);
+    let mut group = c.benchmark_group("get sweep");
+
+    group.bench_function("nested", |b| {
+        b.iter(|| {
+            let mut sum = 0.0;
+            for i in 0..SIZE as i32 {
+                for k in 0..SIZE as i32 {
+                    sum += nested.get(i, k);
+                }
+            }
+            black_box(sum)
+        })
+    });
+
+    group.bench_function("flat", |b| {
+        b.iter(|| {
+            let mut sum = 0.0;
+            for i in 0..SIZE as i32 {
+                for k in 0..SIZE as i32 {
+                    sum += grid.get(i, k);
+                }
+            }
+            black_box(sum)
+        })
+    });
+
+    group.bench_function("flat iter", |b| {
+        b.iter(|| {
+            let sum: f64 = grid
+                .iter()
+                .map(|(i, k, &value)| value + (i + k) as f64)
+                .sum();
+            black_box(sum)
+        })
+    });
+
+    group.finish();
+}
+
+fn sweep_set(c: &mut Criterion) {
+    let mut nested = Nested::<f64>::new(SIZE, SIZE);
+    let mut grid = Grid::<f64>::new(SIZE, SIZE);
+    let mut group = c.benchmark_group("set sweep");
+
+    group.bench_function("nested", |b| {
+        b.iter(|| {
+            for i in 0..SIZE as i32 {
+                for k in 0..SIZE as i32 {
+                    nested.set(i, k, (i + k) as f64);
+                }
+            }
+            black_box(&nested.grid);
+        })
+    });
+
+    group.bench_function("flat", |b| {
+        b.iter(|| {
+            for i in 0..SIZE as i32 {
+                for k in 0..SIZE as i32 {
+                    grid.set(i, k, (i + k) as f64);
+                }
+            }
+            black_box(&grid);
+        })
+    });
+
+    group.finish();
+}
+
+fn sweep_update(c: &mut Criterion) {
+    let mut nested = Nested::<f64>::new(SIZE, SIZE);
+    let mut grid = Grid::<f64>::new(SIZE, SIZE);
+    let mut group = c.benchmark_group("update sweep");
+
+    group.bench_function("nested", |b| {
+        b.iter(|| {
+            for i in 0..SIZE as i32 {
+                for k in 0..SIZE as i32 {
+                    nested.update(i, k, |x| x * 0.5 + 1.0);
+                }
+            }
+            black_box(&nested.grid);
+        })
+    });
+
+    group.bench_function("flat", |b| {
+        b.iter(|| {
+            for i in 0..SIZE as i32 {
+                for k in 0..SIZE as i32 {
+                    grid.update(i, k, |x| x * 0.5 + 1.0);
+                }
+            }
+            black_box(&grid);
+        })
+    });
+
+    group.bench_function("flat map", |b| {
+        b.iter(|| {
+            grid.map(|x| x * 0.5 + 1.0);
+            black_box(&grid);
+        })
+    });
+
+    group.finish();
+}
+
+/// A Gauss-Seidel sweep of a 5-point Laplacian, the shape of the pressure
+/// solver's inner loop, through the checked accessors of both layouts and
+/// through the unchecked ones the solvers' hot loops use for interior cells.
+fn stencil(c: &mut Criterion) {
+    let mut nested_p = Nested::<f64>::new(SIZE, SIZE);
+    let mut nested_rhs = Nested::<f64>::new(SIZE, SIZE);
+    let mut p = Grid::<f64>::new(SIZE, SIZE);
+    let mut rhs = Grid::<f64>::new(SIZE, SIZE);
+    let mut group = c.benchmark_group("stencil");
+
+    for column in nested_rhs.grid.iter_mut() {
+        column.fill(1.0);
+    }
+    rhs.fill(1.0);
+
+    group.bench_function("nested", |b| {
+        b.iter(|| {
+            for i in 1..SIZE as i32 - 1 {
+                for k in 1..SIZE as i32 - 1 {
+                    let sum = nested_p.get(i - 1, k)
+                        + nested_p.get(i + 1, k)
+                        + nested_p.get(i, k - 1)
+                        + nested_p.get(i, k + 1);
+                    nested_p.set(i, k, (sum - nested_rhs.get(i, k)) / 4.0);
+                }
+            }
+            black_box(&nested_p.grid);
+        })
+    });
+
+    group.bench_function("flat", |b| {
+        b.iter(|| {
+            for i in 1..SIZE as i32 - 1 {
+                for k in 1..SIZE as i32 - 1 {
+                    let sum = p.get(i - 1, k) + p.get(i + 1, k) + p.get(i, k - 1) + p.get(i, k + 1);
+                    p.set(i, k, (sum - rhs.get(i, k)) / 4.0);
+                }
+            }
+            black_box(&p);
+        })
+    });
+
+    group.bench_function("flat unchecked", |b| {
+        b.iter(|| {
+            for i in 1..SIZE - 1 {
+                for k in 1..SIZE - 1 {
+                    // SAFETY: 1 <= i, k < SIZE - 1, so every neighbour is in the grid
+                    unsafe {
+                        let sum = p.get_unchecked(i - 1, k)
+                            + p.get_unchecked(i + 1, k)
+                            + p.get_unchecked(i, k - 1)
+                            + p.get_unchecked(i, k + 1);
+                        p.set_unchecked(i, k, (sum - rhs.get_unchecked(i, k)) / 4.0);
+                    }
+                }
+            }
+            black_box(&p);
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, sweep_get, sweep_set, sweep_update, stencil);
+criterion_main!(benches);
@@ -0,0 +1,54 @@
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use wasm::bench::{Domain, PressureSolverKind, ResidualNorm, new_pressure_solver};
+
+// the default 200 rows of the page on a 16:9 screen, with their border
+const WIDTH: usize = 358;
+const HEIGHT: usize = 202;
+const ITERATIONS: usize = 40;
+
+/// The page as a wind tunnel: fluid blown in from the left around a circular
+/// obstacle and out through the open right edge.
+fn wind_tunnel() -> Domain {
+    Domain::new(WIDTH, HEIGHT)
+        .with_circle(WIDTH as f64 / 3.0, HEIGHT as f64 / 2.0, 25.0)
+        .with_open_right()
+        .with_inflow(5.0)
+}
+
+/// One projection of each solver, with the default iteration count.
+fn projection(c: &mut Criterion) {
+    let kinds = [
+        ("projection sor", PressureSolverKind::Sor),
+        ("projection jacobi", PressureSolverKind::Jacobi),
+        (
+            "projection conjugate gradient",
+            PressureSolverKind::ConjugateGradient,
+        ),
+        ("projection multigrid", PressureSolverKind::Multigrid),
+    ];
+
+    for (name, kind) in kinds {
+        let initial = wind_tunnel();
+        let mut domain = initial.clone();
+        let mut solver = new_pressure_solver(kind, WIDTH, HEIGHT);
+
+        c.bench_function(name, |b| {
+            b.iter(|| {
+                domain.u.copy_from(&initial.u);
+                domain.v.copy_from(&initial.v);
+                domain.p.fill(0.0);
+
+                let mut system = domain.system();
+                system.enforce_boundaries();
+                solver.begin(&mut system);
+                for _ in 0..ITERATIONS {
+                    solver.iterate(&mut system);
+                }
+                black_box(system.get_residual(ResidualNorm::Rms))
+            })
+        });
+    }
+}
+
+criterion_group!(benches, projection);
+criterion_main!(benches);
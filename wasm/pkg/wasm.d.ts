@@ -1,18 +1,106 @@
 /* tslint:disable */
 /* eslint-disable */
+export function set_boundaries(left: BoundaryMode, right: BoundaryMode, top: BoundaryMode, bottom: BoundaryMode): void;
+export function set_interpolation(advection: Interpolation, rendering: Interpolation): void;
 export function main(): void;
+export function set_integrator(integrator: Integrator): void;
 export function play(): void;
-export function next_frame(): void;
-export function toggle_playing(): void;
-export function stop(): void;
+export function remove_obstacle(id: number): boolean;
+export function set_dom_obstacles(selectors: string[]): void;
+/**
+ * `points` holds the vertices as `[x0, y0, x1, y1, ...]`.
+ */
+export function add_polygon(points: Float64Array): number | undefined;
 export function print_fluid_info(): void;
-export function run_projection(): void;
-export function run_advection(): void;
+export function set_relaxation(overrelaxation_coefficient: number, jacobi_weight: number): void;
 export function clear_scene(): void;
-export function run_solve_divergence_for_all(): void;
+export function set_diffusion(viscosity: number, diffusion: number): void;
+export function add_circle(x: number, y: number, radius: number): number | undefined;
+export function toggle_show_pressure(): void;
+export function set_dye_color(r: number, g: number, b: number): void;
+export function clear_obstacles(): void;
+export function run_projection(): void;
+export function adjust_to_device_performance(): FpsStats | undefined;
+export function set_tolerance(tolerance: number | null | undefined, residual_norm: ResidualNorm): void;
 export function get_stats(): FpsStats | undefined;
+export function cycle_dye_hue(degrees_per_second: number): void;
+export function clear_painted_obstacles(): void;
+export function set_buoyancy(smoke_weight: number, thermal_expansion: number): void;
+export function set_pointer_mode(mode: PointerMode): void;
+export function set_smoke_dissipation(kind: DissipationKind, value: number): void;
+export function set_vorticity_confinement(epsilon: number): void;
+export function set_obstacle_velocity(id: number, u: number, v: number, angular_velocity: number): boolean;
+export function set_sweep_order(order: SweepOrder): void;
+export function toggle_playing(): void;
+export function run_solve_divergence_for_all(): void;
+export function set_pressure_solver(kind: PressureSolverKind): void;
+export function stop(): void;
+export function set_temperature_dissipation(kind: DissipationKind, value: number): void;
+export function set_cfl_limit(cfl_limit: number | null | undefined, max_substeps: number): void;
+/**
+ * `x` and `y` are the center of the rectangle, `angle` is in radians.
+ */
+export function add_rectangle(x: number, y: number, width: number, height: number, angle: number): number | undefined;
+export function set_paddle(radius?: number | null): void;
+export function set_advection_scheme(scheme: AdvectionScheme): void;
 export function set_stats(resolution: number, subdivisions: number): void;
-export function adjust_to_device_performance(): FpsStats | undefined;
+export function run_advection(): void;
+/**
+ * Polygons are positioned at the average of their vertices.
+ */
+export function move_obstacle(id: number, x: number, y: number, angle: number): boolean;
+export function set_wind_tunnel(speed: number | null | undefined, streaks: number): void;
+export function next_frame(): void;
+export enum AdvectionScheme {
+  SemiLagrangian = 0,
+  MacCormack = 1,
+  Bfecc = 2,
+}
+/**
+ * What happens to the fluid at one edge of the domain.
+ */
+export enum BoundaryMode {
+  NoSlip = 0,
+  FreeSlip = 1,
+  Outflow = 2,
+  Periodic = 3,
+}
+export enum DissipationKind {
+  None = 0,
+  HalfLife = 1,
+  Linear = 2,
+}
+export enum Integrator {
+  Euler = 0,
+  Rk2 = 1,
+  Rk3 = 2,
+}
+export enum Interpolation {
+  Bilinear = 0,
+  Cubic = 1,
+}
+/**
+ * What dragging the pointer over the canvas does.
+ */
+export enum PointerMode {
+  Stir = 0,
+  Paint = 1,
+  Erase = 2,
+}
+export enum PressureSolverKind {
+  Sor = 0,
+  Jacobi = 1,
+  ConjugateGradient = 2,
+  Multigrid = 3,
+}
+export enum ResidualNorm {
+  Max = 0,
+  Rms = 1,
+}
+export enum SweepOrder {
+  ColumnMajor = 0,
+  RedBlack = 1,
+}
 export class FpsStats {
   private constructor();
   free(): void;
@@ -20,4 +108,7 @@ export class FpsStats {
   average_fps: number;
   resolution: number;
   subdivisions: number;
+  iterations: number;
+  residual: number;
+  substeps: number;
 }
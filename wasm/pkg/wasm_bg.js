@@ -212,45 +212,163 @@ function makeMutClosure(arg0, arg1, dtor, f) {
     CLOSURE_DTORS.register(real, state, state);
     return real;
 }
+/**
+ * @param {BoundaryMode} left
+ * @param {BoundaryMode} right
+ * @param {BoundaryMode} top
+ * @param {BoundaryMode} bottom
+ */
+export function set_boundaries(left, right, top, bottom) {
+    wasm.set_boundaries(left, right, top, bottom);
+}
+
+/**
+ * @param {Interpolation} advection
+ * @param {Interpolation} rendering
+ */
+export function set_interpolation(advection, rendering) {
+    wasm.set_interpolation(advection, rendering);
+}
 
 export function main() {
     wasm.main();
 }
 
+/**
+ * @param {Integrator} integrator
+ */
+export function set_integrator(integrator) {
+    wasm.set_integrator(integrator);
+}
+
 export function play() {
     wasm.play();
 }
 
-export function next_frame() {
-    wasm.next_frame();
+/**
+ * @param {number} id
+ * @returns {boolean}
+ */
+export function remove_obstacle(id) {
+    const ret = wasm.remove_obstacle(id);
+    return ret !== 0;
 }
 
-export function toggle_playing() {
-    wasm.toggle_playing();
+function passArrayJsValueToWasm0(array, malloc) {
+    const ptr = malloc(array.length * 4, 4) >>> 0;
+    for (let i = 0; i < array.length; i++) {
+        const add = addToExternrefTable0(array[i]);
+        getDataViewMemory0().setUint32(ptr + 4 * i, add, true);
+    }
+    WASM_VECTOR_LEN = array.length;
+    return ptr;
+}
+/**
+ * @param {string[]} selectors
+ */
+export function set_dom_obstacles(selectors) {
+    const ptr0 = passArrayJsValueToWasm0(selectors, wasm.__wbindgen_malloc);
+    const len0 = WASM_VECTOR_LEN;
+    wasm.set_dom_obstacles(ptr0, len0);
 }
 
-export function stop() {
-    wasm.stop();
+let cachedFloat64ArrayMemory0 = null;
+
+function getFloat64ArrayMemory0() {
+    if (cachedFloat64ArrayMemory0 === null || cachedFloat64ArrayMemory0.byteLength === 0) {
+        cachedFloat64ArrayMemory0 = new Float64Array(wasm.memory.buffer);
+    }
+    return cachedFloat64ArrayMemory0;
 }
 
-export function print_fluid_info() {
-    wasm.print_fluid_info();
+function passArrayF64ToWasm0(arg, malloc) {
+    const ptr = malloc(arg.length * 8, 8) >>> 0;
+    getFloat64ArrayMemory0().set(arg, ptr / 8);
+    WASM_VECTOR_LEN = arg.length;
+    return ptr;
+}
+/**
+ * `points` holds the vertices as `[x0, y0, x1, y1, ...]`.
+ * @param {Float64Array} points
+ * @returns {number | undefined}
+ */
+export function add_polygon(points) {
+    const ptr0 = passArrayF64ToWasm0(points, wasm.__wbindgen_malloc);
+    const len0 = WASM_VECTOR_LEN;
+    const ret = wasm.add_polygon(ptr0, len0);
+    return ret === 0x100000001 ? undefined : ret;
 }
 
-export function run_projection() {
-    wasm.run_projection();
+export function print_fluid_info() {
+    wasm.print_fluid_info();
 }
 
-export function run_advection() {
-    wasm.run_advection();
+/**
+ * @param {number} overrelaxation_coefficient
+ * @param {number} jacobi_weight
+ */
+export function set_relaxation(overrelaxation_coefficient, jacobi_weight) {
+    wasm.set_relaxation(overrelaxation_coefficient, jacobi_weight);
 }
 
 export function clear_scene() {
     wasm.clear_scene();
 }
 
-export function run_solve_divergence_for_all() {
-    wasm.run_solve_divergence_for_all();
+/**
+ * @param {number} viscosity
+ * @param {number} diffusion
+ */
+export function set_diffusion(viscosity, diffusion) {
+    wasm.set_diffusion(viscosity, diffusion);
+}
+
+/**
+ * @param {number} x
+ * @param {number} y
+ * @param {number} radius
+ * @returns {number | undefined}
+ */
+export function add_circle(x, y, radius) {
+    const ret = wasm.add_circle(x, y, radius);
+    return ret === 0x100000001 ? undefined : ret;
+}
+
+export function toggle_show_pressure() {
+    wasm.toggle_show_pressure();
+}
+
+/**
+ * @param {number} r
+ * @param {number} g
+ * @param {number} b
+ */
+export function set_dye_color(r, g, b) {
+    wasm.set_dye_color(r, g, b);
+}
+
+export function clear_obstacles() {
+    wasm.clear_obstacles();
+}
+
+export function run_projection() {
+    wasm.run_projection();
+}
+
+/**
+ * @returns {FpsStats | undefined}
+ */
+export function adjust_to_device_performance() {
+    const ret = wasm.adjust_to_device_performance();
+    return ret === 0 ? undefined : FpsStats.__wrap(ret);
+}
+
+/**
+ * @param {number | null | undefined} tolerance
+ * @param {ResidualNorm} residual_norm
+ */
+export function set_tolerance(tolerance, residual_norm) {
+    wasm.set_tolerance(!isLikeNone(tolerance), isLikeNone(tolerance) ? 0 : tolerance, residual_norm);
 }
 
 /**
@@ -261,6 +379,129 @@ export function get_stats() {
     return ret === 0 ? undefined : FpsStats.__wrap(ret);
 }
 
+/**
+ * @param {number} degrees_per_second
+ */
+export function cycle_dye_hue(degrees_per_second) {
+    wasm.cycle_dye_hue(degrees_per_second);
+}
+
+export function clear_painted_obstacles() {
+    wasm.clear_painted_obstacles();
+}
+
+/**
+ * @param {number} smoke_weight
+ * @param {number} thermal_expansion
+ */
+export function set_buoyancy(smoke_weight, thermal_expansion) {
+    wasm.set_buoyancy(smoke_weight, thermal_expansion);
+}
+
+/**
+ * @param {PointerMode} mode
+ */
+export function set_pointer_mode(mode) {
+    wasm.set_pointer_mode(mode);
+}
+
+/**
+ * @param {DissipationKind} kind
+ * @param {number} value
+ */
+export function set_smoke_dissipation(kind, value) {
+    wasm.set_smoke_dissipation(kind, value);
+}
+
+/**
+ * @param {number} epsilon
+ */
+export function set_vorticity_confinement(epsilon) {
+    wasm.set_vorticity_confinement(epsilon);
+}
+
+/**
+ * @param {number} id
+ * @param {number} u
+ * @param {number} v
+ * @param {number} angular_velocity
+ * @returns {boolean}
+ */
+export function set_obstacle_velocity(id, u, v, angular_velocity) {
+    const ret = wasm.set_obstacle_velocity(id, u, v, angular_velocity);
+    return ret !== 0;
+}
+
+/**
+ * @param {SweepOrder} order
+ */
+export function set_sweep_order(order) {
+    wasm.set_sweep_order(order);
+}
+
+export function toggle_playing() {
+    wasm.toggle_playing();
+}
+
+export function run_solve_divergence_for_all() {
+    wasm.run_solve_divergence_for_all();
+}
+
+/**
+ * @param {PressureSolverKind} kind
+ */
+export function set_pressure_solver(kind) {
+    wasm.set_pressure_solver(kind);
+}
+
+export function stop() {
+    wasm.stop();
+}
+
+/**
+ * @param {DissipationKind} kind
+ * @param {number} value
+ */
+export function set_temperature_dissipation(kind, value) {
+    wasm.set_temperature_dissipation(kind, value);
+}
+
+/**
+ * @param {number | null | undefined} cfl_limit
+ * @param {number} max_substeps
+ */
+export function set_cfl_limit(cfl_limit, max_substeps) {
+    wasm.set_cfl_limit(!isLikeNone(cfl_limit), isLikeNone(cfl_limit) ? 0 : cfl_limit, max_substeps);
+}
+
+/**
+ * `x` and `y` are the center of the rectangle, `angle` is in radians.
+ * @param {number} x
+ * @param {number} y
+ * @param {number} width
+ * @param {number} height
+ * @param {number} angle
+ * @returns {number | undefined}
+ */
+export function add_rectangle(x, y, width, height, angle) {
+    const ret = wasm.add_rectangle(x, y, width, height, angle);
+    return ret === 0x100000001 ? undefined : ret;
+}
+
+/**
+ * @param {number | null} [radius]
+ */
+export function set_paddle(radius) {
+    wasm.set_paddle(!isLikeNone(radius), isLikeNone(radius) ? 0 : radius);
+}
+
+/**
+ * @param {AdvectionScheme} scheme
+ */
+export function set_advection_scheme(scheme) {
+    wasm.set_advection_scheme(scheme);
+}
+
 /**
  * @param {number} resolution
  * @param {number} subdivisions
@@ -269,22 +510,117 @@ export function set_stats(resolution, subdivisions) {
     wasm.set_stats(resolution, subdivisions);
 }
 
+export function run_advection() {
+    wasm.run_advection();
+}
+
 /**
- * @returns {FpsStats | undefined}
+ * Polygons are positioned at the average of their vertices.
+ * @param {number} id
+ * @param {number} x
+ * @param {number} y
+ * @param {number} angle
+ * @returns {boolean}
  */
-export function adjust_to_device_performance() {
-    const ret = wasm.adjust_to_device_performance();
-    return ret === 0 ? undefined : FpsStats.__wrap(ret);
+export function move_obstacle(id, x, y, angle) {
+    const ret = wasm.move_obstacle(id, x, y, angle);
+    return ret !== 0;
+}
+
+/**
+ * @param {number | null | undefined} speed
+ * @param {number} streaks
+ */
+export function set_wind_tunnel(speed, streaks) {
+    wasm.set_wind_tunnel(!isLikeNone(speed), isLikeNone(speed) ? 0 : speed, streaks);
 }
 
-function wasm_bindgen__convert__closures_____invoke__hd16f8b0378c7cdfc(arg0, arg1, arg2) {
-    wasm.wasm_bindgen__convert__closures_____invoke__hd16f8b0378c7cdfc(arg0, arg1, arg2);
+export function next_frame() {
+    wasm.next_frame();
+}
+
+function wasm_bindgen__convert__closures_____invoke__h05a7f8b8cdec275c(arg0, arg1, arg2) {
+    wasm.wasm_bindgen__convert__closures_____invoke__h05a7f8b8cdec275c(arg0, arg1, arg2);
 }
 
-function wasm_bindgen__convert__closures_____invoke__h05c40a4bb5819655(arg0, arg1, arg2) {
-    wasm.wasm_bindgen__convert__closures_____invoke__h05c40a4bb5819655(arg0, arg1, arg2);
+function wasm_bindgen__convert__closures_____invoke__h0b5b49d19e7d5699(arg0, arg1, arg2) {
+    wasm.wasm_bindgen__convert__closures_____invoke__h0b5b49d19e7d5699(arg0, arg1, arg2);
 }
 
+/**
+ * @enum {0 | 1 | 2}
+ */
+export const AdvectionScheme = Object.freeze({
+    SemiLagrangian: 0, "0": "SemiLagrangian",
+    MacCormack: 1, "1": "MacCormack",
+    Bfecc: 2, "2": "Bfecc",
+});
+/**
+ * What happens to the fluid at one edge of the domain.
+ * @enum {0 | 1 | 2 | 3}
+ */
+export const BoundaryMode = Object.freeze({
+    NoSlip: 0, "0": "NoSlip",
+    FreeSlip: 1, "1": "FreeSlip",
+    Outflow: 2, "2": "Outflow",
+    Periodic: 3, "3": "Periodic",
+});
+/**
+ * @enum {0 | 1 | 2}
+ */
+export const DissipationKind = Object.freeze({
+    None: 0, "0": "None",
+    HalfLife: 1, "1": "HalfLife",
+    Linear: 2, "2": "Linear",
+});
+/**
+ * @enum {0 | 1 | 2}
+ */
+export const Integrator = Object.freeze({
+    Euler: 0, "0": "Euler",
+    Rk2: 1, "1": "Rk2",
+    Rk3: 2, "2": "Rk3",
+});
+/**
+ * @enum {0 | 1}
+ */
+export const Interpolation = Object.freeze({
+    Bilinear: 0, "0": "Bilinear",
+    Cubic: 1, "1": "Cubic",
+});
+/**
+ * What dragging the pointer over the canvas does.
+ * @enum {0 | 1 | 2}
+ */
+export const PointerMode = Object.freeze({
+    Stir: 0, "0": "Stir",
+    Paint: 1, "1": "Paint",
+    Erase: 2, "2": "Erase",
+});
+/**
+ * @enum {0 | 1 | 2 | 3}
+ */
+export const PressureSolverKind = Object.freeze({
+    Sor: 0, "0": "Sor",
+    Jacobi: 1, "1": "Jacobi",
+    ConjugateGradient: 2, "2": "ConjugateGradient",
+    Multigrid: 3, "3": "Multigrid",
+});
+/**
+ * @enum {0 | 1}
+ */
+export const ResidualNorm = Object.freeze({
+    Max: 0, "0": "Max",
+    Rms: 1, "1": "Rms",
+});
+/**
+ * @enum {0 | 1}
+ */
+export const SweepOrder = Object.freeze({
+    ColumnMajor: 0, "0": "ColumnMajor",
+    RedBlack: 1, "1": "RedBlack",
+});
+
 const FpsStatsFinalization = (typeof FinalizationRegistry === 'undefined')
     ? { register: () => {}, unregister: () => {} }
     : new FinalizationRegistry(ptr => wasm.__wbg_fpsstats_free(ptr >>> 0, 1));
@@ -349,6 +685,45 @@ export class FpsStats {
     set subdivisions(arg0) {
         wasm.__wbg_set_fpsstats_subdivisions(this.__wbg_ptr, arg0);
     }
+    /**
+     * @returns {number}
+     */
+    get iterations() {
+        const ret = wasm.__wbg_get_fpsstats_iterations(this.__wbg_ptr);
+        return ret >>> 0;
+    }
+    /**
+     * @param {number} arg0
+     */
+    set iterations(arg0) {
+        wasm.__wbg_set_fpsstats_iterations(this.__wbg_ptr, arg0);
+    }
+    /**
+     * @returns {number}
+     */
+    get residual() {
+        const ret = wasm.__wbg_get_fpsstats_residual(this.__wbg_ptr);
+        return ret;
+    }
+    /**
+     * @param {number} arg0
+     */
+    set residual(arg0) {
+        wasm.__wbg_set_fpsstats_residual(this.__wbg_ptr, arg0);
+    }
+    /**
+     * @returns {number}
+     */
+    get substeps() {
+        const ret = wasm.__wbg_get_fpsstats_substeps(this.__wbg_ptr);
+        return ret >>> 0;
+    }
+    /**
+     * @param {number} arg0
+     */
+    set substeps(arg0) {
+        wasm.__wbg_set_fpsstats_substeps(this.__wbg_ptr, arg0);
+    }
 }
 if (Symbol.dispose) FpsStats.prototype[Symbol.dispose] = FpsStats.prototype.free;
 
@@ -372,6 +747,15 @@ export function __wbg___wbindgen_number_get_a20bf9b85341449d(arg0, arg1) {
     getDataViewMemory0().setInt32(arg0 + 4 * 0, !isLikeNone(ret), true);
 };
 
+export function __wbg___wbindgen_string_get_e4f06c90489ad01b(arg0, arg1) {
+    const obj = arg1;
+    const ret = typeof(obj) === 'string' ? obj : undefined;
+    var ptr1 = isLikeNone(ret) ? 0 : passStringToWasm0(ret, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
+    var len1 = WASM_VECTOR_LEN;
+    getDataViewMemory0().setInt32(arg0 + 4 * 1, len1, true);
+    getDataViewMemory0().setInt32(arg0 + 4 * 0, ptr1, true);
+};
+
 export function __wbg___wbindgen_throw_b855445ff6a94295(arg0, arg1) {
     throw new Error(getStringFromWasm0(arg0, arg1));
 };
@@ -388,6 +772,11 @@ export function __wbg_beginPath_ae4169e263573dcd(arg0) {
     arg0.beginPath();
 };
 
+export function __wbg_bottom_48779afa7b750239(arg0) {
+    const ret = arg0.bottom;
+    return ret;
+};
+
 export function __wbg_call_e762c39fa8ea36bf() { return handleError(function (arg0, arg1) {
     const ret = arg0.call(arg1);
     return ret;
@@ -440,6 +829,11 @@ export function __wbg_fill_c1b94332a3f5eecc(arg0) {
     arg0.fill();
 };
 
+export function __wbg_getBoundingClientRect_eb2f68e504025fb4(arg0) {
+    const ret = arg0.getBoundingClientRect();
+    return ret;
+};
+
 export function __wbg_getContext_0b80ccb9547db509() { return handleError(function (arg0, arg1, arg2) {
     const ret = arg0.getContext(getStringFromWasm0(arg1, arg2));
     return isLikeNone(ret) ? 0 : addToExternrefTable0(ret);
@@ -450,6 +844,11 @@ export function __wbg_height_119077665279308c(arg0) {
     return ret;
 };
 
+export function __wbg_height_ba3edd16b1f48a4a(arg0) {
+    const ret = arg0.height;
+    return ret;
+};
+
 export function __wbg_innerHeight_686136a20f2f5575() { return handleError(function (arg0) {
     const ret = arg0.innerHeight;
     return ret;
@@ -471,6 +870,17 @@ export function __wbg_instanceof_CanvasRenderingContext2d_c0728747cf1e699c(arg0)
     return ret;
 };
 
+export function __wbg_instanceof_Element_437534ce3e96fe49(arg0) {
+    let result;
+    try {
+        result = arg0 instanceof Element;
+    } catch (_) {
+        result = false;
+    }
+    const ret = result;
+    return ret;
+};
+
 export function __wbg_instanceof_HtmlCanvasElement_3e2e95b109dae976(arg0) {
     let result;
     try {
@@ -493,6 +903,21 @@ export function __wbg_instanceof_Window_4846dbb3de56c84c(arg0) {
     return ret;
 };
 
+export function __wbg_item_c9164a2843281946(arg0, arg1) {
+    const ret = arg0.item(arg1 >>> 0);
+    return isLikeNone(ret) ? 0 : addToExternrefTable0(ret);
+};
+
+export function __wbg_left_899de713c50d5346(arg0) {
+    const ret = arg0.left;
+    return ret;
+};
+
+export function __wbg_length_84d8751e9d1868a5(arg0) {
+    const ret = arg0.length;
+    return ret;
+};
+
 export function __wbg_lineTo_1e83b5f2f38f15f9(arg0, arg1, arg2) {
     arg0.lineTo(arg1, arg2);
 };
@@ -530,6 +955,11 @@ export function __wbg_performance_e8315b5ae987e93f(arg0) {
     return isLikeNone(ret) ? 0 : addToExternrefTable0(ret);
 };
 
+export function __wbg_querySelectorAll_e1a6c50956fdb6a2() { return handleError(function (arg0, arg1, arg2) {
+    const ret = arg0.querySelectorAll(getStringFromWasm0(arg1, arg2));
+    return ret;
+}, arguments) };
+
 export function __wbg_querySelector_f2dcf5aaab20ba86() { return handleError(function (arg0, arg1, arg2) {
     const ret = arg0.querySelector(getStringFromWasm0(arg1, arg2));
     return isLikeNone(ret) ? 0 : addToExternrefTable0(ret);
@@ -540,6 +970,11 @@ export function __wbg_requestAnimationFrame_7ecf8bfece418f08() { return handleEr
     return ret;
 }, arguments) };
 
+export function __wbg_right_bec501ed000bfe81(arg0) {
+    const ret = arg0.right;
+    return ret;
+};
+
 export function __wbg_set_fillStyle_c41ec913f9f22a0c(arg0, arg1, arg2) {
     arg0.fillStyle = getStringFromWasm0(arg1, arg2);
 };
@@ -568,6 +1003,10 @@ export function __wbg_set_onresize_a470d6ab8bf341f4(arg0, arg1) {
     arg0.onresize = arg1;
 };
 
+export function __wbg_set_onscroll_308b64ce916997f3(arg0, arg1) {
+    arg0.onscroll = arg1;
+};
+
 export function __wbg_set_strokeStyle_475a0c2a522e1c7e(arg0, arg1, arg2) {
     arg0.strokeStyle = getStringFromWasm0(arg1, arg2);
 };
@@ -576,6 +1015,11 @@ export function __wbg_set_width_dcc02c61dd01cff6(arg0, arg1) {
     arg0.width = arg1 >>> 0;
 };
 
+export function __wbg_shiftKey_02a93ca3ce31a4f4(arg0) {
+    const ret = arg0.shiftKey;
+    return ret;
+};
+
 export function __wbg_stack_0ed75d68575b0f3c(arg0, arg1) {
     const ret = arg1.stack;
     const ptr1 = passStringToWasm0(ret, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
@@ -613,6 +1057,11 @@ export function __wbg_timeStamp_39af1ea0e2a34f79(arg0) {
     return ret;
 };
 
+export function __wbg_top_e4eeead6b19051fb(arg0) {
+    const ret = arg0.top;
+    return ret;
+};
+
 export function __wbg_userAgent_b20949aa6be940a6() { return handleError(function (arg0, arg1) {
     const ret = arg1.userAgent;
     const ptr1 = passStringToWasm0(ret, wasm.__wbindgen_malloc, wasm.__wbindgen_realloc);
@@ -626,27 +1075,32 @@ export function __wbg_width_9ea2df52b5d2c909(arg0) {
     return ret;
 };
 
+export function __wbg_width_cd308a6e89422ce8(arg0) {
+    const ret = arg0.width;
+    return ret;
+};
+
 export function __wbindgen_cast_2241b6af4c4b2941(arg0, arg1) {
     // Cast intrinsic for `Ref(String) -> Externref`.
     const ret = getStringFromWasm0(arg0, arg1);
     return ret;
 };
 
-export function __wbindgen_cast_80543d3aec4e6329(arg0, arg1) {
-    // Cast intrinsic for `Closure(Closure { dtor_idx: 30, function: Function { arguments: [NamedExternref("PointerEvent")], shim_idx: 31, ret: Unit, inner_ret: Some(Unit) }, mutable: true }) -> Externref`.
-    const ret = makeMutClosure(arg0, arg1, wasm.wasm_bindgen__closure__destroy__h334319f47aa8154f, wasm_bindgen__convert__closures_____invoke__hd16f8b0378c7cdfc);
+export function __wbindgen_cast_5ac8080986d5d9d1(arg0, arg1) {
+    // Cast intrinsic for `Closure(Closure { dtor_idx: 99, function: Function { arguments: [NamedExternref("Event")], shim_idx: 102, ret: Unit, inner_ret: Some(Unit) }, mutable: true }) -> Externref`.
+    const ret = makeMutClosure(arg0, arg1, wasm.wasm_bindgen__closure__destroy__h0c085b2f2b9b8627, wasm_bindgen__convert__closures_____invoke__h05a7f8b8cdec275c);
     return ret;
 };
 
-export function __wbindgen_cast_b2f86a9123567793(arg0, arg1) {
-    // Cast intrinsic for `Closure(Closure { dtor_idx: 30, function: Function { arguments: [NamedExternref("Event")], shim_idx: 31, ret: Unit, inner_ret: Some(Unit) }, mutable: true }) -> Externref`.
-    const ret = makeMutClosure(arg0, arg1, wasm.wasm_bindgen__closure__destroy__h334319f47aa8154f, wasm_bindgen__convert__closures_____invoke__hd16f8b0378c7cdfc);
+export function __wbindgen_cast_d34ecfb17de4c5f0(arg0, arg1) {
+    // Cast intrinsic for `Closure(Closure { dtor_idx: 99, function: Function { arguments: [NamedExternref("PointerEvent")], shim_idx: 102, ret: Unit, inner_ret: Some(Unit) }, mutable: true }) -> Externref`.
+    const ret = makeMutClosure(arg0, arg1, wasm.wasm_bindgen__closure__destroy__h0c085b2f2b9b8627, wasm_bindgen__convert__closures_____invoke__h05a7f8b8cdec275c);
     return ret;
 };
 
-export function __wbindgen_cast_fac144aa0aa0631f(arg0, arg1) {
-    // Cast intrinsic for `Closure(Closure { dtor_idx: 30, function: Function { arguments: [F64], shim_idx: 34, ret: Unit, inner_ret: Some(Unit) }, mutable: true }) -> Externref`.
-    const ret = makeMutClosure(arg0, arg1, wasm.wasm_bindgen__closure__destroy__h334319f47aa8154f, wasm_bindgen__convert__closures_____invoke__h05c40a4bb5819655);
+export function __wbindgen_cast_d974844e795cf660(arg0, arg1) {
+    // Cast intrinsic for `Closure(Closure { dtor_idx: 99, function: Function { arguments: [F64], shim_idx: 100, ret: Unit, inner_ret: Some(Unit) }, mutable: true }) -> Externref`.
+    const ret = makeMutClosure(arg0, arg1, wasm.wasm_bindgen__closure__destroy__h0c085b2f2b9b8627, wasm_bindgen__convert__closures_____invoke__h0b5b49d19e7d5699);
     return ret;
 };
 
@@ -1,29 +1,64 @@
 /* tslint:disable */
 /* eslint-disable */
 export const memory: WebAssembly.Memory;
-export const main: () => void;
-export const play: () => void;
-export const next_frame: () => void;
-export const toggle_playing: () => void;
-export const stop: () => void;
-export const print_fluid_info: () => void;
-export const run_projection: () => void;
-export const run_advection: () => void;
-export const clear_scene: () => void;
-export const run_solve_divergence_for_all: () => void;
 export const __wbg_fpsstats_free: (a: number, b: number) => void;
 export const __wbg_get_fpsstats_average_fps: (a: number) => number;
-export const __wbg_set_fpsstats_average_fps: (a: number, b: number) => void;
+export const __wbg_get_fpsstats_iterations: (a: number) => number;
+export const __wbg_get_fpsstats_residual: (a: number) => number;
 export const __wbg_get_fpsstats_resolution: (a: number) => number;
-export const __wbg_set_fpsstats_resolution: (a: number, b: number) => void;
 export const __wbg_get_fpsstats_subdivisions: (a: number) => number;
+export const __wbg_get_fpsstats_substeps: (a: number) => number;
+export const __wbg_set_fpsstats_average_fps: (a: number, b: number) => void;
+export const __wbg_set_fpsstats_iterations: (a: number, b: number) => void;
+export const __wbg_set_fpsstats_residual: (a: number, b: number) => void;
+export const __wbg_set_fpsstats_resolution: (a: number, b: number) => void;
 export const __wbg_set_fpsstats_subdivisions: (a: number, b: number) => void;
+export const __wbg_set_fpsstats_substeps: (a: number, b: number) => void;
+export const add_circle: (a: number, b: number, c: number) => number;
+export const add_polygon: (a: number, b: number) => number;
+export const add_rectangle: (a: number, b: number, c: number, d: number, e: number) => number;
+export const adjust_to_device_performance: () => number;
 export const get_stats: () => number;
+export const move_obstacle: (a: number, b: number, c: number, d: number) => number;
+export const remove_obstacle: (a: number) => number;
+export const set_advection_scheme: (a: number) => void;
+export const set_boundaries: (a: number, b: number, c: number, d: number) => void;
+export const set_cfl_limit: (a: number, b: number, c: number) => void;
+export const set_dom_obstacles: (a: number, b: number) => void;
+export const set_dye_color: (a: number, b: number, c: number) => void;
+export const set_integrator: (a: number) => void;
+export const set_interpolation: (a: number, b: number) => void;
+export const set_obstacle_velocity: (a: number, b: number, c: number, d: number) => number;
+export const set_paddle: (a: number, b: number) => void;
+export const set_pointer_mode: (a: number) => void;
+export const set_pressure_solver: (a: number) => void;
+export const set_smoke_dissipation: (a: number, b: number) => void;
 export const set_stats: (a: number, b: number) => void;
-export const adjust_to_device_performance: () => number;
-export const wasm_bindgen__convert__closures_____invoke__hd16f8b0378c7cdfc: (a: number, b: number, c: any) => void;
-export const wasm_bindgen__closure__destroy__h334319f47aa8154f: (a: number, b: number) => void;
-export const wasm_bindgen__convert__closures_____invoke__h05c40a4bb5819655: (a: number, b: number, c: number) => void;
+export const set_sweep_order: (a: number) => void;
+export const set_temperature_dissipation: (a: number, b: number) => void;
+export const set_tolerance: (a: number, b: number, c: number) => void;
+export const set_wind_tunnel: (a: number, b: number, c: number) => void;
+export const clear_painted_obstacles: () => void;
+export const cycle_dye_hue: (a: number) => void;
+export const set_vorticity_confinement: (a: number) => void;
+export const toggle_playing: () => void;
+export const toggle_show_pressure: () => void;
+export const set_buoyancy: (a: number, b: number) => void;
+export const set_diffusion: (a: number, b: number) => void;
+export const set_relaxation: (a: number, b: number) => void;
+export const play: () => void;
+export const stop: () => void;
+export const next_frame: () => void;
+export const main: () => void;
+export const clear_obstacles: () => void;
+export const clear_scene: () => void;
+export const print_fluid_info: () => void;
+export const run_advection: () => void;
+export const run_projection: () => void;
+export const run_solve_divergence_for_all: () => void;
+export const wasm_bindgen__convert__closures_____invoke__h05a7f8b8cdec275c: (a: number, b: number, c: any) => void;
+export const wasm_bindgen__closure__destroy__h0c085b2f2b9b8627: (a: number, b: number) => void;
+export const wasm_bindgen__convert__closures_____invoke__h0b5b49d19e7d5699: (a: number, b: number, c: number) => void;
 export const __wbindgen_malloc: (a: number, b: number) => number;
 export const __wbindgen_realloc: (a: number, b: number, c: number, d: number) => number;
 export const __wbindgen_exn_store: (a: number) => void;
@@ -0,0 +1,167 @@
+//! The grids of a pressure solve on their own, without a `Fluid` and its
+//! canvas, shared by the solver tests and the benches.
+
+use crate::fluid::ResidualNorm;
+use crate::grid::Grid;
+use crate::pressure::{PressureSolverKind, PressureSystem, SweepOrder, Wrap, new_pressure_solver};
+
+/// A box of fluid with a one cell border, laid out like the grids of a
+/// `Fluid`, and a velocity field that is far from divergence free.
+#[derive(Debug, Clone)]
+pub struct Domain {
+    pub u: Grid<f64>,
+    pub v: Grid<f64>,
+    pub p: Grid<f64>,
+    pub b: Grid<u8>,
+    pub solid_u: Grid<f64>,
+    pub solid_v: Grid<f64>,
+    pub face_u: Grid<f64>,
+    pub face_v: Grid<f64>,
+    pub wrap: Wrap,
+}
+
+impl Domain {
+    /// A `width` by `height` grid, border included, closed on every side.
+    pub fn new(width: usize, height: usize) -> Domain {
+        let mut b = Grid::new(width, height);
+
+        for (_, _, fluid) in b.interior_mut() {
+            *fluid = 1;
+        }
+
+        let mut u = Grid::new(width + 1, height);
+        let mut v = Grid::new(width, height + 1);
+
+        for (i, k, u) in u.iter_mut() {
+            *u = f64::sin(i as f64 * 0.7 + k as f64 * 1.3) + ((i * k) % 3) as f64;
+        }
+        for (i, k, v) in v.iter_mut() {
+            *v = f64::cos(i as f64 * 1.1 - k as f64 * 0.4) - ((i + k) % 2) as f64;
+        }
+
+        let mut domain = Domain {
+            u,
+            v,
+            p: Grid::new(width, height),
+            b,
+            solid_u: Grid::new(width + 1, height),
+            solid_v: Grid::new(width, height + 1),
+            face_u: Grid::new(width + 1, height),
+            face_v: Grid::new(width, height + 1),
+            wrap: Wrap::default(),
+        };
+
+        domain.fill_faces();
+        domain
+    }
+
+    /// Makes the cells whose center is within `radius` of `(x, y)` solid.
+    pub fn with_circle(mut self, x: f64, y: f64, radius: f64) -> Domain {
+        for (i, k, fluid) in self.b.interior_mut() {
+            if f64::hypot(i as f64 + 0.5 - x, k as f64 + 0.5 - y) <= radius {
+                *fluid = 0;
+            }
+        }
+
+        self.fill_faces();
+        self
+    }
+
+    /// Opens the right edge: its border cells become fluid held at zero
+    /// pressure, as with `BoundaryMode::Outflow`.
+    pub fn with_open_right(mut self) -> Domain {
+        let right = self.b.width() as i32 - 1;
+
+        for k in 1..self.b.height() as i32 - 1 {
+            self.b.set(right, k, 1);
+        }
+
+        self.fill_faces();
+        self
+    }
+
+    /// Blows fluid in through the left wall at `speed`, as in a wind
+    /// tunnel, whose outflow is `with_open_right`.
+    pub fn with_inflow(mut self, speed: f64) -> Domain {
+        for k in 1..self.b.height() as i32 - 1 {
+            self.solid_u.set(0, k, speed);
+            self.solid_u.set(1, k, speed);
+        }
+
+        self
+    }
+
+    /// Joins the opposite edges of the wrapped axes, as with
+    /// `BoundaryMode::Periodic`.
+    pub fn with_wrap(mut self, x: bool, y: bool) -> Domain {
+        let (width, height) = (self.b.width() as i32, self.b.height() as i32);
+
+        self.wrap = Wrap { x, y };
+        self.wrap.fill_ghosts(&mut self.b);
+        self.fill_faces();
+
+        if x {
+            for k in 0..height {
+                self.u.set(width - 1, k, self.u.get(1, k));
+            }
+        }
+        if y {
+            for i in 0..width {
+                self.v.set(i, height - 1, self.v.get(i, 1));
+            }
+        }
+
+        self
+    }
+
+    /// Whether both copies of every face where wrapped edges meet agree.
+    pub fn has_joined_seams(&self) -> bool {
+        let (width, height) = (self.b.width() as i32, self.b.height() as i32);
+
+        (!self.wrap.x || (0..height).all(|k| self.u.get(1, k) == self.u.get(width - 1, k)))
+            && (!self.wrap.y || (0..width).all(|i| self.v.get(i, 1) == self.v.get(i, height - 1)))
+    }
+
+    fn fill_faces(&mut self) {
+        for (i, k, open) in self.face_u.iter_mut() {
+            *open = (self.b.get(i - 1, k) * self.b.get(i, k)) as f64;
+        }
+        for (i, k, open) in self.face_v.iter_mut() {
+            *open = (self.b.get(i, k - 1) * self.b.get(i, k)) as f64;
+        }
+    }
+
+    pub fn system(&mut self) -> PressureSystem<'_> {
+        PressureSystem {
+            u: &mut self.u,
+            v: &mut self.v,
+            p: &mut self.p,
+            b: &self.b,
+            solid_u: &self.solid_u,
+            solid_v: &self.solid_v,
+            face_u: &self.face_u,
+            face_v: &self.face_v,
+            overrelaxation_coefficient: 1.9,
+            jacobi_weight: 0.8,
+            sweep_order: SweepOrder::ColumnMajor,
+            pressure_scale: 1.0,
+            wrap: self.wrap,
+        }
+    }
+
+    /// Residual left after `iterations` of `kind`, relative to the initial one.
+    pub fn solve(&mut self, kind: PressureSolverKind, iterations: usize) -> f64 {
+        let mut solver = new_pressure_solver(kind, self.b.width(), self.b.height());
+        let mut system = self.system();
+
+        system.enforce_boundaries();
+        let initial = system.get_residual(ResidualNorm::Rms);
+
+        solver.begin(&mut system);
+        for _ in 0..iterations {
+            solver.iterate(&mut system);
+        }
+
+        system.get_residual(ResidualNorm::Rms) / initial
+    }
+}
@@ -1,34 +1,251 @@
 use crate::grid::Grid;
+use crate::obstacle::Obstacle;
+use crate::pressure::{
+    PressureSolver, PressureSolverKind, PressureSystem, Sor, SweepOrder, Wrap, new_pressure_solver,
+};
+use wasm_bindgen::prelude::*;
 
 #[derive(Debug, Clone)]
 pub enum Field {
     U,
     V,
     S,
+    P,
+    T,
+    Dye(usize),
 }
+
+pub const DYE_CHANNELS: usize = 3;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DissipationKind {
+    None,
+    HalfLife,
+    Linear,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdvectionScheme {
+    SemiLagrangian,
+    MacCormack,
+    Bfecc,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Integrator {
+    Euler,
+    Rk2,
+    Rk3,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    Bilinear,
+    Cubic,
+}
+
+/// What happens to the fluid at one edge of the domain.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundaryMode {
+    NoSlip,   // solid wall that drags the fluid along it to a stop
+    FreeSlip, // solid wall the fluid slides along freely
+    Outflow,  // open edge the fluid leaves through unhindered
+    Periodic, // wraps around, the fluid leaving it comes back in at the opposite edge
+}
+
+impl BoundaryMode {
+    fn is_open(&self) -> bool {
+        matches!(self, BoundaryMode::Outflow)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Boundaries {
+    pub left: BoundaryMode,
+    pub right: BoundaryMode,
+    pub top: BoundaryMode,
+    pub bottom: BoundaryMode,
+}
+
+impl Boundaries {
+    /// A periodic edge is joined to the opposite one, which becomes periodic
+    /// too.
+    pub fn new(
+        left: BoundaryMode,
+        right: BoundaryMode,
+        top: BoundaryMode,
+        bottom: BoundaryMode,
+    ) -> Boundaries {
+        let pair = |a: BoundaryMode, b: BoundaryMode| {
+            if a == BoundaryMode::Periodic || b == BoundaryMode::Periodic {
+                (BoundaryMode::Periodic, BoundaryMode::Periodic)
+            } else {
+                (a, b)
+            }
+        };
+
+        let (left, right) = pair(left, right);
+        let (top, bottom) = pair(top, bottom);
+
+        Boundaries {
+            left,
+            right,
+            top,
+            bottom,
+        }
+    }
+
+    pub fn wrap(&self) -> Wrap {
+        let is_periodic = |mode: BoundaryMode| mode == BoundaryMode::Periodic;
+
+        Wrap {
+            x: is_periodic(self.left) || is_periodic(self.right),
+            y: is_periodic(self.top) || is_periodic(self.bottom),
+        }
+    }
+}
+
+impl Default for Boundaries {
+    fn default() -> Boundaries {
+        Boundaries::new(
+            BoundaryMode::NoSlip,
+            BoundaryMode::NoSlip,
+            BoundaryMode::NoSlip,
+            BoundaryMode::NoSlip,
+        )
+    }
+}
+
+/// Constant flow blown in through the left edge and out through the right one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindTunnel {
+    pub speed: f64,     // in pixels/s
+    pub streaks: usize, // number of dye streaks carried in, 0 for none
+    pub streak_smoke: f64,
+    pub streak_color: [f64; DYE_CHANNELS],
+}
+
+/// How fast an advected quantity fades, independently of the step rate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dissipation {
+    None,
+    HalfLife(f64),               // seconds for the value to halve
+    Linear(f64),                 // amount removed per second
+    HalfLifeAndLinear(f64, f64), // a half life, then an amount removed per second
+}
+
+impl Dissipation {
+    pub fn new(kind: DissipationKind, value: f64) -> Dissipation {
+        match kind {
+            DissipationKind::None => Dissipation::None,
+            DissipationKind::HalfLife => Dissipation::HalfLife(value),
+            DissipationKind::Linear => Dissipation::Linear(value),
+        }
+    }
+
+    pub fn apply(&self, value: f64, delta_t: f64) -> f64 {
+        match *self {
+            Dissipation::None => value,
+            Dissipation::HalfLife(half_life) if half_life > 0.0 => {
+                value * f64::powf(0.5, delta_t / half_life)
+            }
+            Dissipation::HalfLife(_) => 0.0,
+            Dissipation::Linear(rate) => f64::max(value - rate * delta_t, 0.0),
+            Dissipation::HalfLifeAndLinear(half_life, rate) => Dissipation::Linear(rate).apply(
+                Dissipation::HalfLife(half_life).apply(value, delta_t),
+                delta_t,
+            ),
+        }
+    }
+}
+
+impl Default for Dissipation {
+    /// Same fade as the original `/ 1.05 - 0.1` per step at 60 steps per second.
+    fn default() -> Dissipation {
+        Dissipation::HalfLifeAndLinear(std::f64::consts::LN_2 / (60.0 * f64::ln(1.05)), 0.1 * 60.0)
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResidualNorm {
+    Max,
+    Rms,
+}
+
 pub trait FluidSimulation {
     fn projection(&mut self);
     fn advection(&mut self);
+    fn get_pressure(&self) -> &Grid<f64>;
     fn interpolate(&self, x: f64, y: f64, field: Field) -> f64;
+    fn interpolate_with(&self, x: f64, y: f64, field: Field, interpolation: Interpolation) -> f64;
     fn get_grid_indices_from_xy(&self, x: f64, y: f64, field: Option<&Field>) -> (i32, i32);
     fn get_xy_from_grid_indices(&self, x: i32, y: i32, field: Option<&Field>) -> (f64, f64);
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct Fluid {
-    pub u: Grid<f64>,      // velocity in x direction
-    pub v: Grid<f64>,      // velocity in y direction
-    pub b: Grid<u8>,       // obstacles
-    pub s: Grid<f64>,      // smoke (density)
-    pub next_u: Grid<f64>, // velocity in x direction
-    pub next_v: Grid<f64>, // velocity in y direction
-    pub next_s: Grid<f64>, // smoke (density)
+    pub u: Grid<f64>,                   // velocity in x direction
+    pub v: Grid<f64>,                   // velocity in y direction
+    pub b: Grid<u8>,                    // obstacles
+    pub solid_u: Grid<f64>,             // velocity of the obstacles on the u faces
+    pub solid_v: Grid<f64>,             // velocity of the obstacles on the v faces
+    pub face_u: Grid<f64>,              // open fraction of the u faces
+    pub face_v: Grid<f64>,              // open fraction of the v faces
+    pub painted: Grid<u8>,              // cells painted solid by hand
+    pub has_painted: bool,              // whether any cell of `painted` is set
+    pub s: Grid<f64>,                   // smoke (density)
+    pub p: Grid<f64>,                   // pressure
+    pub w: Grid<f64>,                   // vorticity
+    pub t: Grid<f64>,                   // temperature
+    pub dye: [Grid<f64>; DYE_CHANNELS], // smoke density of each rgb channel
+    pub next_u: Grid<f64>,              // velocity in x direction
+    pub next_v: Grid<f64>,              // velocity in y direction
+    pub next_s: Grid<f64>,              // smoke (density)
+    pub next_t: Grid<f64>,              // temperature
+    pub next_dye: [Grid<f64>; DYE_CHANNELS],
+    pub previous_u: Grid<f64>, // state before the last step, blended with when rendering
+    pub previous_v: Grid<f64>,
+    pub previous_s: Grid<f64>,
+    pub previous_dye: [Grid<f64>; DYE_CHANNELS],
+    pub corrected: Grid<f64>, // scratch for the MacCormack and BFECC corrections
 
     pub n: usize,
     pub square_size: f64,
     pub n_iterations: usize,
     pub delta_t: f64,
+    pub cfl_limit: Option<f64>, // cells crossed per substep, None disables it
+    pub max_substeps: usize,
+    pub last_substeps: usize,
+    pub advection_scheme: AdvectionScheme,
+    pub integrator: Integrator, // used to trace the characteristics during advection
+    pub advection_interpolation: Interpolation,
     pub overrelaxation_coefficient: f64,
+    pub jacobi_weight: f64,
+    pub sweep_order: SweepOrder,
+    pub pressure_solver: Box<dyn PressureSolver>,
+    pub tolerance: Option<f64>, // stop projection once the residual is below this
+    pub residual_norm: ResidualNorm,
+    pub last_iterations: usize,
+    pub last_residual: f64,
+    pub vorticity_confinement: f64, // epsilon, 0 disables it
+    pub viscosity: f64,             // in pixels^2/s, 0 disables it
+    pub diffusion: f64,             // dye diffusivity in pixels^2/s, 0 disables it
+    pub diffusion_iterations: usize,
+    pub smoke_dissipation: Dissipation,
+    pub temperature_dissipation: Dissipation,
+    pub smoke_weight: f64, // downward force per unit of smoke, 0 disables it
+    pub thermal_expansion: f64, // upward force per unit of temperature, 0 disables it
+    pub boundaries: Boundaries,
+    pub wind_tunnel: Option<WindTunnel>,
+    pub edges_before_wind_tunnel: (BoundaryMode, BoundaryMode), // restored when it stops
+    pub obstacles: Vec<Obstacle>,
+    pub next_obstacle_id: u32,
     pub grid_width: usize,
     pub grid_height: usize,
     pub max_squares: usize,
@@ -64,19 +281,54 @@ impl Fluid {
         let v = Grid::new(grid_width + 2 * n, grid_height + 1 + 2 * n);
         let s = Grid::new(grid_width + 2 * n, grid_height + 2 * n);
         let b = Grid::new(grid_width + 2 * n, grid_height + 2 * n);
-
+        let p = Grid::new(grid_width + 2 * n, grid_height + 2 * n);
+        let w = Grid::new(grid_width + 2 * n, grid_height + 2 * n);
+        let t = Grid::new(grid_width + 2 * n, grid_height + 2 * n);
+        let dye = std::array::from_fn(|_| Grid::new(grid_width + 2 * n, grid_height + 2 * n));
+
+        let solid_u = Grid::new(u.width(), u.height());
+        let solid_v = Grid::new(v.width(), v.height());
+        let face_u = Grid::new(u.width(), u.height());
+        let face_v = Grid::new(v.width(), v.height());
+        let painted = Grid::new(b.width(), b.height());
         let next_u = Grid::new(u.width(), u.height());
         let next_v = Grid::new(v.width(), v.height());
         let next_s = Grid::new(s.width(), s.height());
+        let next_t = Grid::new(t.width(), t.height());
+        let next_dye = std::array::from_fn(|_| Grid::new(s.width(), s.height()));
+        let previous_u = Grid::new(u.width(), u.height());
+        let previous_v = Grid::new(v.width(), v.height());
+        let previous_s = Grid::new(s.width(), s.height());
+        let previous_dye = std::array::from_fn(|_| Grid::new(s.width(), s.height()));
+        let corrected = Grid::new(u.width(), v.height());
+
+        let pressure_solver = new_pressure_solver(PressureSolverKind::Sor, b.width(), b.height());
 
         Fluid {
             u,
             v,
             b,
+            solid_u,
+            solid_v,
+            face_u,
+            face_v,
+            painted,
+            has_painted: false,
             s,
+            p,
+            w,
+            t,
+            dye,
             next_u,
             next_v,
             next_s,
+            next_t,
+            next_dye,
+            previous_u,
+            previous_v,
+            previous_s,
+            previous_dye,
+            corrected,
             block_offset,
             grid_width,
             grid_height,
@@ -86,71 +338,912 @@ impl Fluid {
             square_size,
             n_iterations,
             delta_t,
+            cfl_limit: None,
+            max_substeps: 4,
+            last_substeps: 1,
+            advection_scheme: AdvectionScheme::SemiLagrangian,
+            integrator: Integrator::Euler,
+            advection_interpolation: Interpolation::Bilinear,
             overrelaxation_coefficient,
+            jacobi_weight: 0.8,
+            sweep_order: SweepOrder::ColumnMajor,
+            pressure_solver,
+            tolerance: None,
+            residual_norm: ResidualNorm::Max,
+            last_iterations: 0,
+            last_residual: 0.0,
+            vorticity_confinement: 0.0,
+            viscosity: 0.0,
+            diffusion: 0.0,
+            diffusion_iterations: 20,
+            smoke_dissipation: Dissipation::default(),
+            temperature_dissipation: Dissipation::default(),
+            smoke_weight: 0.0,
+            thermal_expansion: 0.0,
+            boundaries: Boundaries::default(),
+            wind_tunnel: None,
+            edges_before_wind_tunnel: (BoundaryMode::NoSlip, BoundaryMode::NoSlip),
+            obstacles: vec![],
+            next_obstacle_id: 1,
         }
     }
 
+    /// Resets the obstacles to the edges of the domain: the border cells of a
+    /// wall are solid, those of an open edge are fluid held at zero pressure.
+    /// Those of a periodic edge are left solid until `update_obstacles` copies
+    /// the far side into them.
     pub fn fill_edges_with_obstacles(&mut self) {
-        self.b.fill(1);
+        let top = self.boundaries.top.is_open() as u8;
+        let bottom = self.boundaries.bottom.is_open() as u8;
+        let left = self.boundaries.left.is_open() as u8;
+        let right = self.boundaries.right.is_open() as u8;
+
+        let width = self.b.width() as i32;
+        let height = self.b.height() as i32;
+
+        // the side edges own the corners
+        for (i, k, fluid) in self.b.iter_mut() {
+            *fluid = if i == 0 {
+                left
+            } else if i == width - 1 {
+                right
+            } else if k == 0 {
+                top
+            } else if k == height - 1 {
+                bottom
+            } else {
+                1
+            };
+        }
+    }
+
+    /// Rebuilds `b` from the edges, the painted cells and every obstacle, and
+    /// sets the velocity on the faces the obstacles cover to theirs.
+    pub fn update_obstacles(&mut self) {
+        self.fill_edges_with_obstacles();
+
+        self.solid_u.fill(0.0);
+        self.solid_v.fill(0.0);
+        self.fill_inflow();
+
+        self.has_painted = false;
+
+        for (i, k, _) in self.painted.iter().filter(|&(_, _, &painted)| painted != 0) {
+            self.has_painted = true;
+            self.b.set(i, k, 0);
+            self.u.set(i, k, 0.0);
+            self.u.set(i + 1, k, 0.0);
+            self.v.set(i, k, 0.0);
+            self.v.set(i, k + 1, 0.0);
+        }
+
+        for obstacle in self.obstacles.iter() {
+            let cells: Vec<(i32, i32)> = self.get_obstacle_cells(obstacle).collect();
+
+            for (i, k) in cells {
+                self.b.set(i, k, 0);
 
-        for i in 0..self.b.width() {
-            let i = i as i32;
-            self.b.set(i, 0, 0);
-            self.b.set(i, self.b.height() as i32 - 1, 0);
+                for i in [i, i + 1] {
+                    let (x, y) = self.get_xy_from_grid_indices(i, k, Some(&Field::U));
+                    let (u, _) = obstacle.get_velocity_at(x, y);
+
+                    self.u.set(i, k, u);
+                    self.solid_u.set(i, k, u);
+                }
+
+                for k in [k, k + 1] {
+                    let (x, y) = self.get_xy_from_grid_indices(i, k, Some(&Field::V));
+                    let (_, v) = obstacle.get_velocity_at(x, y);
+
+                    self.v.set(i, k, v);
+                    self.solid_v.set(i, k, v);
+                }
+            }
         }
 
-        for k in 0..self.b.height() {
-            let k = k as i32;
-            self.b.set(0, k, 0);
-            self.b.set(self.b.width() as i32 - 1, k, 0);
+        // the ghosts of a wrapped edge are whatever is on the far side
+        self.boundaries.wrap().fill_ghosts(&mut self.b);
+
+        // smoke does not linger inside the walls that closed over it
+        for (i, k, _) in self.b.interior().filter(|&(_, _, &fluid)| fluid == 0) {
+            self.s.set(i, k, 0.0);
+            self.t.set(i, k, 0.0);
+
+            for dye in self.dye.iter_mut() {
+                dye.set(i, k, 0.0);
+            }
         }
+
+        self.fill_faces();
     }
 
-    pub fn solve_divergence_for_all(&mut self) {
-        for i in 0..self.b.width() {
-            for k in 0..self.b.height() {
-                let i = i as i32;
-                let k = k as i32;
-
-                if self.b.get(i, k) == 0 {
-                    self.u.set(i, k, 0.0);
-                    self.v.set(i, k, 0.0);
-                    continue;
+    /// The cells whose center is inside `obstacle`, i.e. those it makes solid.
+    pub fn get_obstacle_cells<'a>(
+        &'a self,
+        obstacle: &'a Obstacle,
+    ) -> impl Iterator<Item = (i32, i32)> + 'a {
+        let (min_x, min_y, max_x, max_y) = obstacle.get_bounds();
+        let (min_i, min_k) = self.get_grid_indices_from_xy(min_x, min_y, Some(&Field::S));
+        let (max_i, max_k) = self.get_grid_indices_from_xy(max_x, max_y, Some(&Field::S));
+
+        self.b
+            .region((min_i, min_k), (max_i + 1, max_k + 1))
+            .filter(move |&(i, k)| {
+                let (x, y) = self.get_xy_from_grid_indices(i, k, Some(&Field::S));
+                obstacle.contains(x, y)
+            })
+    }
+
+    /// Sets the open fraction of every face: closed next to a solid cell, and
+    /// only partly open where an obstacle cuts across it between two fluid
+    /// cells, so curved boundaries are not staircases for the projection. The
+    /// covered part of a face moves with the obstacle.
+    fn fill_faces(&mut self) {
+        const SAMPLES: usize = 8;
+        // smaller openings are closed, their tiny weights make huge corrections
+        const MIN_OPEN: f64 = 0.1;
+
+        for (i, k, open) in self.face_u.iter_mut() {
+            *open = (self.b.get(i - 1, k) * self.b.get(i, k)) as f64;
+        }
+
+        for (i, k, open) in self.face_v.iter_mut() {
+            *open = (self.b.get(i, k - 1) * self.b.get(i, k)) as f64;
+        }
+
+        for obstacle in self.obstacles.iter() {
+            let (min_x, min_y, max_x, max_y) = obstacle.get_bounds();
+            let (min_i, min_k) = self.get_grid_indices_from_xy(min_x, min_y, None);
+            let (max_i, max_k) = self.get_grid_indices_from_xy(max_x, max_y, None);
+
+            // fraction of the segment from (x, y) along (dx, dy) outside the obstacle
+            let get_open = |x: f64, y: f64, dx: f64, dy: f64| {
+                let inside = (0..SAMPLES)
+                    .filter(|&j| {
+                        let t = (j as f64 + 0.5) / SAMPLES as f64;
+                        obstacle.contains(x + t * dx, y + t * dy)
+                    })
+                    .count();
+
+                1.0 - inside as f64 / SAMPLES as f64
+            };
+
+            let half = self.square_size / 2.0;
+
+            for i in min_i.max(0)..=max_i + 1 {
+                for k in min_k.max(0)..=max_k + 1 {
+                    let open = self.face_u.get(i, k);
+
+                    if open > 0.0 {
+                        let (x, y) = self.get_xy_from_grid_indices(i, k, Some(&Field::U));
+                        let fraction = get_open(x, y - half, 0.0, self.square_size);
+
+                        if fraction < 1.0 {
+                            let (u, _) = obstacle.get_velocity_at(x, y);
+                            let open = open * fraction;
+                            let open = if open < MIN_OPEN { 0.0 } else { open };
+
+                            self.face_u.set(i, k, open);
+                            self.solid_u.set(i, k, u);
+
+                            if open == 0.0 {
+                                self.u.set(i, k, u);
+                            }
+                        }
+                    }
+
+                    let open = self.face_v.get(i, k);
+
+                    if open > 0.0 {
+                        let (x, y) = self.get_xy_from_grid_indices(i, k, Some(&Field::V));
+                        let fraction = get_open(x - half, y, self.square_size, 0.0);
+
+                        if fraction < 1.0 {
+                            let (_, v) = obstacle.get_velocity_at(x, y);
+                            let open = open * fraction;
+                            let open = if open < MIN_OPEN { 0.0 } else { open };
+
+                            self.face_v.set(i, k, open);
+                            self.solid_v.set(i, k, v);
+
+                            if open == 0.0 {
+                                self.v.set(i, k, v);
+                            }
+                        }
+                    }
                 }
-                if self.b.get(i - 1, k) == 0 {
-                    self.u.set(i, k, 0.0);
+            }
+        }
+
+        self.join_seam_faces();
+    }
+
+    /// Gives both copies of the faces where periodic edges meet the smaller
+    /// open fraction, so an obstacle cutting one of them cuts the other too.
+    fn join_seam_faces(&mut self) {
+        let wrap = self.boundaries.wrap();
+        let (width, height) = (self.b.width() as i32, self.b.height() as i32);
+
+        let join = |face: &mut Grid<f64>,
+                    solid: &mut Grid<f64>,
+                    velocity: &mut Grid<f64>,
+                    a: (i32, i32),
+                    b: (i32, i32)| {
+            let (from, to) = if face.get(a.0, a.1) <= face.get(b.0, b.1) {
+                (a, b)
+            } else {
+                (b, a)
+            };
+
+            let open = face.get(from.0, from.1);
+
+            face.set(to.0, to.1, open);
+            solid.set(to.0, to.1, solid.get(from.0, from.1));
+
+            if open == 0.0 {
+                velocity.set(to.0, to.1, velocity.get(from.0, from.1));
+            }
+        };
+
+        if wrap.x {
+            for k in 0..height {
+                join(
+                    &mut self.face_u,
+                    &mut self.solid_u,
+                    &mut self.u,
+                    (1, k),
+                    (width - 1, k),
+                );
+            }
+        }
+
+        if wrap.y {
+            for i in 0..width {
+                join(
+                    &mut self.face_v,
+                    &mut self.solid_v,
+                    &mut self.v,
+                    (i, 1),
+                    (i, height - 1),
+                );
+            }
+        }
+    }
+
+    /// Adds an obstacle and returns the id it was given.
+    pub fn add_obstacle(&mut self, obstacle: Obstacle) -> u32 {
+        self.add_obstacles(vec![obstacle])[0]
+    }
+
+    /// Adds several obstacles at once, rebuilding `b` only once, and returns
+    /// the ids they were given.
+    pub fn add_obstacles(&mut self, obstacles: Vec<Obstacle>) -> Vec<u32> {
+        self.replace_obstacles(&[], obstacles)
+    }
+
+    /// Removes the obstacles with `ids` and adds `obstacles` in their place,
+    /// giving them the removed ids first so that refreshing a set of obstacles
+    /// does not use up new ones. Returns the ids they were given.
+    pub fn replace_obstacles(&mut self, ids: &[u32], obstacles: Vec<Obstacle>) -> Vec<u32> {
+        self.obstacles.retain(|o| !ids.contains(&o.id));
+
+        let mut reused = ids.iter().copied();
+
+        let ids = obstacles
+            .into_iter()
+            .map(|mut obstacle| {
+                obstacle.id = reused.next().unwrap_or_else(|| {
+                    self.next_obstacle_id += 1;
+                    self.next_obstacle_id - 1
+                });
+
+                let id = obstacle.id;
+                self.obstacles.push(obstacle);
+                id
+            })
+            .collect();
+
+        self.update_obstacles();
+
+        ids
+    }
+
+    /// Returns false if there is no obstacle with this id.
+    pub fn move_obstacle(&mut self, id: u32, x: f64, y: f64, angle: f64) -> bool {
+        let Some(obstacle) = self.obstacles.iter_mut().find(|o| o.id == id) else {
+            return false;
+        };
+
+        obstacle.x = x;
+        obstacle.y = y;
+        obstacle.angle = angle;
+
+        self.update_obstacles();
+        true
+    }
+
+    /// Returns false if there is no obstacle with this id.
+    pub fn set_obstacle_velocity(
+        &mut self,
+        id: u32,
+        velocity: (f64, f64),
+        angular_velocity: f64,
+    ) -> bool {
+        let Some(obstacle) = self.obstacles.iter_mut().find(|o| o.id == id) else {
+            return false;
+        };
+
+        obstacle.velocity = velocity;
+        obstacle.angular_velocity = angular_velocity;
+
+        true
+    }
+
+    /// Advances the moving obstacles by one step and rebuilds `b` around them.
+    pub fn move_obstacles(&mut self) {
+        if !self.obstacles.iter().any(|o| o.is_moving()) {
+            return;
+        }
+
+        for obstacle in self.obstacles.iter_mut() {
+            obstacle.step(self.delta_t);
+        }
+
+        self.update_obstacles();
+    }
+
+    /// Returns false if there is no obstacle with this id.
+    pub fn remove_obstacle(&mut self, id: u32) -> bool {
+        let count = self.obstacles.len();
+
+        self.remove_obstacles(&[id]);
+
+        self.obstacles.len() != count
+    }
+
+    pub fn remove_obstacles(&mut self, ids: &[u32]) {
+        self.obstacles.retain(|o| !ids.contains(&o.id));
+        self.update_obstacles();
+    }
+
+    /// Paints the cells within `radius` of the segment from `from` to `to`
+    /// solid, or erases the painted cells there when `solid` is false, so a
+    /// fast stroke leaves no gaps between two pointer events.
+    pub fn paint_obstacle(&mut self, from: (i32, i32), to: (i32, i32), radius: i32, solid: bool) {
+        let steps = i32::max((to.0 - from.0).abs(), (to.1 - from.1).abs()).max(1);
+
+        for step in 0..=steps {
+            let t = step as f64 / steps as f64;
+            let center_i = (from.0 as f64 + (to.0 - from.0) as f64 * t).round() as i32;
+            let center_k = (from.1 as f64 + (to.1 - from.1) as f64 * t).round() as i32;
+
+            let brush = self.painted.region(
+                (center_i - radius, center_k - radius),
+                (center_i + radius, center_k + radius),
+            );
+
+            for (i, k) in brush {
+                let (di, dk) = (i - center_i, k - center_k);
+
+                if di * di + dk * dk <= radius * radius {
+                    self.painted.set(i, k, solid as u8);
                 }
-                if self.b.get(i, k - 1) == 0 {
-                    self.v.set(i, k, 0.0);
+            }
+        }
+
+        self.update_obstacles();
+    }
+
+    pub fn clear_obstacles(&mut self) {
+        self.obstacles.clear();
+        self.update_obstacles();
+    }
+
+    pub fn set_boundaries(&mut self, boundaries: Boundaries) {
+        self.boundaries = boundaries;
+        self.update_obstacles();
+        self.apply_boundaries();
+    }
+
+    /// Turns the left edge into an inflow and the right one into an outflow,
+    /// or puts back the modes they had before when `wind_tunnel` is `None`.
+    pub fn set_wind_tunnel(&mut self, wind_tunnel: Option<WindTunnel>) {
+        let was_blowing = self.wind_tunnel.is_some();
+
+        self.wind_tunnel = wind_tunnel;
+
+        let (left, right) = match wind_tunnel {
+            Some(_) => {
+                if !was_blowing {
+                    self.edges_before_wind_tunnel = (self.boundaries.left, self.boundaries.right);
                 }
 
-                self.solve_divergence(i, k);
+                (BoundaryMode::NoSlip, BoundaryMode::Outflow)
             }
+            None if was_blowing => self.edges_before_wind_tunnel,
+            None => (self.boundaries.left, self.boundaries.right),
+        };
+
+        self.set_boundaries(Boundaries::new(
+            left,
+            right,
+            self.boundaries.top,
+            self.boundaries.bottom,
+        ));
+    }
+
+    /// Holds the faces of the left wall at the wind tunnel speed.
+    fn fill_inflow(&mut self) {
+        let speed = self
+            .wind_tunnel
+            .map_or(0.0, |wind_tunnel| wind_tunnel.speed);
+
+        let height = self.solid_u.height() as i32;
+
+        for k in 0..height {
+            let speed = if k == 0 || k == height - 1 {
+                0.0
+            } else {
+                speed
+            };
+
+            self.solid_u.set(0, k, speed);
+            self.solid_u.set(1, k, speed);
+        }
+    }
+
+    /// Fills the border cells from the fluid next to them so that advection
+    /// and interpolation see the configured edges.
+    pub fn apply_boundaries(&mut self) {
+        let width = self.b.width() as i32;
+        let height = self.b.height() as i32;
+        let wrap = self.boundaries.wrap();
+
+        if wrap.x {
+            self.wrap_edges(false);
+        } else {
+            self.apply_edge(self.boundaries.left, false, 0, 1);
+            self.apply_edge(self.boundaries.right, false, width - 1, width - 2);
+        }
+
+        if wrap.y {
+            self.wrap_edges(true);
+        } else {
+            self.apply_edge(self.boundaries.top, true, 0, 1);
+            self.apply_edge(self.boundaries.bottom, true, height - 1, height - 2);
+        }
+
+        if let Some(wind_tunnel) = self.wind_tunnel {
+            self.fill_streaks(&wind_tunnel);
         }
     }
 
-    fn solve_divergence(&mut self, i: i32, k: i32) {
-        let b0 = self.b.get(i - 1, k);
-        let b1 = self.b.get(i + 1, k);
-        let b2 = self.b.get(i, k - 1);
-        let b3 = self.b.get(i, k + 1);
+    /// Sets the smoke blown in by the wind tunnel in the left border, evenly
+    /// spaced streaks two cells thick.
+    fn fill_streaks(&mut self, wind_tunnel: &WindTunnel) {
+        let height = self.s.height() as i32 - 2;
+        let spacing = height as f64 / wind_tunnel.streaks.max(1) as f64;
 
-        let b = b0 + b1 + b2 + b3;
+        for k in 1..=height {
+            let position = (k - 1) as f64 / spacing;
+            let offset = (position - position.floor() - 0.5) * spacing;
 
-        if b == 0 {
-            return;
+            let smoke = if wind_tunnel.streaks > 0 && offset.abs() < 1.0 {
+                wind_tunnel.streak_smoke
+            } else {
+                0.0
+            };
+
+            self.s.set(0, k, smoke);
+
+            for (channel, c) in wind_tunnel.streak_color.iter().enumerate() {
+                self.dye[channel].set(0, k, smoke * c);
+            }
+        }
+    }
+
+    /// Fills the border row (or column) `ghost` of an edge from the row
+    /// `inside` it.
+    fn apply_edge(&mut self, mode: BoundaryMode, rows: bool, ghost: i32, inside: i32) {
+        // the normal velocity lives on the faces, one further out than the cells
+        // on the far edge
+        let (normal_ghost, normal_inside) = if ghost == 0 {
+            (0, 1)
+        } else {
+            (ghost + 1, ghost)
+        };
+
+        let (normal, tangential) = if rows {
+            (&mut self.v, &mut self.u)
+        } else {
+            (&mut self.u, &mut self.v)
+        };
+
+        match mode {
+            // the wall velocity is interpolated halfway between the ghost and
+            // the fluid, so mirroring the fluid makes it zero
+            BoundaryMode::NoSlip => {
+                copy_line(tangential, inside, ghost, rows);
+                scale_line(tangential, ghost, -1.0, rows);
+            }
+            BoundaryMode::FreeSlip => copy_line(tangential, inside, ghost, rows),
+            // both edges at once, see `wrap_edges`
+            BoundaryMode::Periodic => {}
+            BoundaryMode::Outflow => {
+                copy_line(tangential, inside, ghost, rows);
+                copy_line(normal, normal_inside, normal_ghost, rows);
+
+                for grid in [&mut self.s, &mut self.t]
+                    .into_iter()
+                    .chain(self.dye.iter_mut())
+                {
+                    copy_line(grid, inside, ghost, rows);
+                }
+            }
+        }
+    }
+
+    /// Joins the left and right edges, or the top and bottom ones when `rows`
+    /// is set: the border rows become copies of the rows on the far side, and
+    /// the two copies of the face where the edges meet agree.
+    fn wrap_edges(&mut self, rows: bool) {
+        let size = if rows {
+            self.b.height()
+        } else {
+            self.b.width()
+        } as i32;
+
+        let (normal, tangential) = if rows {
+            (&mut self.v, &mut self.u)
+        } else {
+            (&mut self.u, &mut self.v)
+        };
+
+        // advection sets both copies of the seam on its own
+        average_lines(normal, 1, size - 1, rows);
+        copy_line(normal, size - 2, 0, rows);
+        copy_line(normal, 2, size, rows);
+
+        for grid in [tangential, &mut self.s, &mut self.t]
+            .into_iter()
+            .chain(self.dye.iter_mut())
+        {
+            copy_line(grid, size - 2, 0, rows);
+            copy_line(grid, 1, size - 1, rows);
+        }
+    }
+
+    fn get_pressure_system(&mut self) -> (&mut Box<dyn PressureSolver>, PressureSystem<'_>) {
+        let system = PressureSystem {
+            u: &mut self.u,
+            v: &mut self.v,
+            p: &mut self.p,
+            b: &self.b,
+            solid_u: &self.solid_u,
+            solid_v: &self.solid_v,
+            face_u: &self.face_u,
+            face_v: &self.face_v,
+            overrelaxation_coefficient: self.overrelaxation_coefficient,
+            jacobi_weight: self.jacobi_weight,
+            sweep_order: self.sweep_order,
+            pressure_scale: self.square_size / self.delta_t,
+            wrap: self.boundaries.wrap(),
+        };
+
+        (&mut self.pressure_solver, system)
+    }
+
+    /// Remembers the rendered fields before they are stepped, for
+    /// `interpolate_between`.
+    pub fn save_previous_state(&mut self) {
+        self.previous_u.copy_from(&self.u);
+        self.previous_v.copy_from(&self.v);
+        self.previous_s.copy_from(&self.s);
+
+        for channel in 0..DYE_CHANNELS {
+            self.previous_dye[channel].copy_from(&self.dye[channel]);
+        }
+    }
+
+    /// Samples `field` blended between its state before the last step
+    /// (`alpha` = 0) and its current one (`alpha` = 1). Fields without a saved
+    /// state are sampled as they are now.
+    pub fn interpolate_between(
+        &self,
+        x: f64,
+        y: f64,
+        field: Field,
+        interpolation: Interpolation,
+        alpha: f64,
+    ) -> f64 {
+        let current = self.interpolate_grid(self.get_field(&field), x, y, &field, interpolation);
+
+        if alpha >= 1.0 {
+            return current;
+        }
+
+        let previous = match field {
+            Field::U => &self.previous_u,
+            Field::V => &self.previous_v,
+            Field::S => &self.previous_s,
+            Field::Dye(channel) => &self.previous_dye[channel],
+            Field::P | Field::T => return current,
+        };
+
+        let previous = self.interpolate_grid(previous, x, y, &field, interpolation);
+
+        previous + (current - previous) * alpha
+    }
+
+    /// Largest velocity component on any face, in pixels/s.
+    pub fn get_max_velocity(&self) -> f64 {
+        self.u
+            .iter()
+            .chain(self.v.iter())
+            .fold(0.0, |max, (_, _, velocity)| f64::max(max, velocity.abs()))
+    }
+
+    /// Number of substeps `delta_t` has to be split into so that the fluid
+    /// crosses at most `cfl_limit` cells in each.
+    pub fn get_substeps(&self) -> usize {
+        let Some(cfl_limit) = self.cfl_limit else {
+            return 1;
+        };
+
+        let cfl = self.get_max_velocity() * self.delta_t / self.square_size;
+
+        ((cfl / cfl_limit).ceil() as usize).clamp(1, self.max_substeps.max(1))
+    }
+
+    pub fn set_pressure_solver(&mut self, kind: PressureSolverKind) {
+        self.pressure_solver = new_pressure_solver(kind, self.b.width(), self.b.height());
+    }
+
+    /// One SOR sweep, as a single step of `projection`.
+    pub fn solve_divergence_for_all(&mut self) {
+        self.p.fill(0.0);
+
+        let (_, mut system) = self.get_pressure_system();
+
+        system.enforce_boundaries();
+        Sor::sweep(&mut system);
+    }
+
+    fn get_field(&self, field: &Field) -> &Grid<f64> {
+        match field {
+            Field::U => &self.u,
+            Field::V => &self.v,
+            Field::S => &self.s,
+            Field::P => &self.p,
+            Field::T => &self.t,
+            Field::Dye(channel) => &self.dye[*channel],
+        }
+    }
+
+    fn get_next_field(&self, field: &Field) -> &Grid<f64> {
+        match field {
+            Field::U => &self.next_u,
+            Field::V => &self.next_v,
+            Field::S => &self.next_s,
+            Field::T => &self.next_t,
+            Field::Dye(channel) => &self.next_dye[*channel],
+            Field::P => unreachable!("pressure is not advected"),
         }
+    }
 
-        let divergence = (self.get_divergence(i, k) * self.overrelaxation_coefficient) / b as f64;
+    fn get_next_field_mut(&mut self, field: &Field) -> &mut Grid<f64> {
+        match field {
+            Field::U => &mut self.next_u,
+            Field::V => &mut self.next_v,
+            Field::S => &mut self.next_s,
+            Field::T => &mut self.next_t,
+            Field::Dye(channel) => &mut self.next_dye[*channel],
+            Field::P => unreachable!("pressure is not advected"),
+        }
+    }
 
-        self.u.update(i, k, |v| v + divergence * b0 as f64);
-        self.u.update(i + 1, k, |v| v - divergence * b1 as f64);
-        self.v.update(i, k, |v| v + divergence * b2 as f64);
-        self.v.update(i, k + 1, |v| v - divergence * b3 as f64);
+    /// Whether a sample of `field` lies between two fluid cells (or in one, for
+    /// cell centered fields) and is therefore advected.
+    fn is_advected(&self, i: i32, k: i32, field: &Field) -> bool {
+        match field {
+            Field::U => self.b.get(i, k) != 0 && self.b.get(i - 1, k) != 0,
+            Field::V => self.b.get(i, k) != 0 && self.b.get(i, k - 1) != 0,
+            _ => self.b.get(i, k) != 0,
+        }
     }
 
-    fn get_divergence(&self, i: i32, k: i32) -> f64 {
-        self.u.get(i + 1, k) - self.u.get(i, k) + self.v.get(i, k + 1) - self.v.get(i, k)
+    /// Reads a sample of `grid`, wrapping around the periodic edges.
+    fn sample(&self, grid: &Grid<f64>, i: i32, k: i32) -> f64 {
+        let (i, k) = self
+            .boundaries
+            .wrap()
+            .cell(i, k, self.b.width(), self.b.height());
+
+        grid.get(i, k)
+    }
+
+    fn interpolate_grid(
+        &self,
+        grid: &Grid<f64>,
+        x: f64,
+        y: f64,
+        field: &Field,
+        interpolation: Interpolation,
+    ) -> f64 {
+        let (i, k) = self.get_grid_indices_from_xy(x, y, Some(field));
+        let (grid_x, grid_y) = self.get_xy_from_grid_indices(i, k, Some(field));
+
+        let xx = x - grid_x;
+        let yy = y - grid_y;
+
+        match interpolation {
+            Interpolation::Bilinear => {
+                let w_x = 1.0 - xx / self.square_size;
+                let w_y = 1.0 - yy / self.square_size;
+
+                let new_value_bot =
+                    w_x * self.sample(grid, i, k) + (1.0 - w_x) * self.sample(grid, i + 1, k);
+                let new_value_top = w_x * self.sample(grid, i, k + 1)
+                    + (1.0 - w_x) * self.sample(grid, i + 1, k + 1);
+
+                w_y * new_value_bot + (1.0 - w_y) * new_value_top
+            }
+            Interpolation::Cubic => {
+                let t_x = xx / self.square_size;
+                let t_y = yy / self.square_size;
+
+                let rows: [f64; 4] = std::array::from_fn(|row| {
+                    let kk = k - 1 + row as i32;
+
+                    catmull_rom(
+                        self.sample(grid, i - 1, kk),
+                        self.sample(grid, i, kk),
+                        self.sample(grid, i + 1, kk),
+                        self.sample(grid, i + 2, kk),
+                        t_x,
+                    )
+                });
+
+                let value = catmull_rom(rows[0], rows[1], rows[2], rows[3], t_y);
+
+                // clamping to the four nearest samples keeps the cubic from
+                // overshooting, so smoke never goes negative
+                let (min, max) = self.get_interpolation_range(grid, x, y, field);
+
+                value.clamp(min, max)
+            }
+        }
+    }
+
+    /// Smallest and largest of the four samples `interpolate_grid` blends.
+    fn get_interpolation_range(
+        &self,
+        grid: &Grid<f64>,
+        x: f64,
+        y: f64,
+        field: &Field,
+    ) -> (f64, f64) {
+        let (i, k) = self.get_grid_indices_from_xy(x, y, Some(field));
+
+        [
+            self.sample(grid, i, k),
+            self.sample(grid, i + 1, k),
+            self.sample(grid, i, k + 1),
+            self.sample(grid, i + 1, k + 1),
+        ]
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &v| {
+            (min.min(v), max.max(v))
+        })
+    }
+
+    fn get_velocity(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            self.interpolate(x, y, Field::U),
+            self.interpolate(x, y, Field::V),
+        )
+    }
+
+    /// Moves a point along the current velocity field for `delta_t`.
+    fn trace(&self, x: f64, y: f64, delta_t: f64) -> (f64, f64) {
+        self.integrate(x, y, self.get_velocity(x, y), delta_t)
+    }
+
+    /// Same as `trace` when the velocity `(u, v)` at the starting point is
+    /// already known.
+    fn integrate(&self, x: f64, y: f64, velocity: (f64, f64), delta_t: f64) -> (f64, f64) {
+        integrate_position(self.integrator, (x, y), velocity, delta_t, |x, y| {
+            self.get_velocity(x, y)
+        })
+    }
+
+    /// Second pass of the MacCormack and BFECC schemes. The semi-Lagrangian
+    /// result in the next grid is advected forward again to estimate its error,
+    /// and the corrected value is clamped to the samples it was interpolated
+    /// from so that it cannot overshoot.
+    fn correct_advection(&mut self, field: Field) {
+        // `corrected` is sized for the largest field, so clear what this one leaves
+        self.corrected.fill(0.0);
+
+        for (i, k) in self.get_next_field(&field).indices() {
+            let value = self.get_field(&field).get(i, k);
+
+            if !self.is_advected(i, k, &field) {
+                self.corrected.set(i, k, value);
+                continue;
+            }
+
+            let (x, y) = self.get_xy_from_grid_indices(i, k, Some(&field));
+            let (next_x, next_y) = self.trace(x, y, self.delta_t);
+
+            let round_trip = self.interpolate_grid(
+                self.get_next_field(&field),
+                next_x,
+                next_y,
+                &field,
+                self.advection_interpolation,
+            );
+
+            self.corrected.set(i, k, value + (value - round_trip) / 2.0);
+        }
+
+        for (i, k) in self.get_next_field(&field).indices() {
+            if !self.is_advected(i, k, &field) {
+                continue;
+            }
+
+            let (x, y) = self.get_xy_from_grid_indices(i, k, Some(&field));
+            let (previous_x, previous_y) = self.trace(x, y, -self.delta_t);
+
+            let value = match self.advection_scheme {
+                AdvectionScheme::MacCormack => {
+                    self.get_next_field(&field).get(i, k) + self.corrected.get(i, k)
+                        - self.get_field(&field).get(i, k)
+                }
+                _ => self.interpolate_grid(
+                    &self.corrected,
+                    previous_x,
+                    previous_y,
+                    &field,
+                    self.advection_interpolation,
+                ),
+            };
+
+            let (min, max) = self.get_interpolation_range(
+                self.get_field(&field),
+                previous_x,
+                previous_y,
+                &field,
+            );
+
+            self.get_next_field_mut(&field)
+                .set(i, k, value.clamp(min, max));
+        }
+    }
+
+    /// Fades the advected smoke and temperature.
+    fn dissipate(&mut self) {
+        for (i, k, _) in self.b.iter().filter(|&(_, _, &fluid)| fluid != 0) {
+            let s = self.next_s.get(i, k);
+            let faded = self.smoke_dissipation.apply(s, self.delta_t);
+
+            self.next_s.set(i, k, faded);
+
+            // the dye fades at the same rate as the smoke so its colour is kept
+            let fade = if s > 0.0 { faded / s } else { 0.0 };
+
+            for channel in 0..DYE_CHANNELS {
+                self.next_dye[channel].update(i, k, |d| d * fade);
+            }
+        }
+
+        self.next_t.zip_with(&self.b, |t, fluid| {
+            if fluid == 0 {
+                t
+            } else {
+                self.temperature_dissipation.apply(t, self.delta_t)
+            }
+        });
     }
 
     fn advect_u(&mut self, i: i32, k: i32) {
@@ -164,10 +1257,14 @@ impl Fluid {
         let u = self.u.get(i, k);
         let v = self.interpolate(x, y, Field::V);
 
-        let previous_x = x - u * self.delta_t;
-        let previous_y = y - v * self.delta_t;
+        let (previous_x, previous_y) = self.integrate(x, y, (u, v), -self.delta_t);
 
-        let next_val = self.interpolate(previous_x, previous_y, Field::U);
+        let next_val = self.interpolate_with(
+            previous_x,
+            previous_y,
+            Field::U,
+            self.advection_interpolation,
+        );
 
         self.next_u.set(i, k, next_val);
     }
@@ -183,10 +1280,14 @@ impl Fluid {
         let u = self.interpolate(x, y, Field::U);
         let v = self.v.get(i, k);
 
-        let previous_x = x - u * self.delta_t;
-        let previous_y = y - v * self.delta_t;
+        let (previous_x, previous_y) = self.integrate(x, y, (u, v), -self.delta_t);
 
-        let next_val = self.interpolate(previous_x, previous_y, Field::V);
+        let next_val = self.interpolate_with(
+            previous_x,
+            previous_y,
+            Field::V,
+            self.advection_interpolation,
+        );
 
         self.next_v.set(i, k, next_val);
     }
@@ -197,21 +1298,217 @@ impl Fluid {
         let u = self.interpolate(x, y, Field::U);
         let v = self.interpolate(x, y, Field::V);
 
-        let previous_x = x - u * self.delta_t;
-        let previous_y = y - v * self.delta_t;
+        let (previous_x, previous_y) = self.integrate(x, y, (u, v), -self.delta_t);
 
-        let next_val = f64::max(
-            self.interpolate(previous_x, previous_y, Field::S) / 1.05 - 0.1,
-            0.0,
+        let next_val = self.interpolate_with(
+            previous_x,
+            previous_y,
+            Field::S,
+            self.advection_interpolation,
         );
 
         self.next_s.set(i, k, next_val);
+
+        for channel in 0..DYE_CHANNELS {
+            let dye = self.interpolate_with(
+                previous_x,
+                previous_y,
+                Field::Dye(channel),
+                self.advection_interpolation,
+            );
+            self.next_dye[channel].set(i, k, dye);
+        }
+    }
+
+    /// Adds `amount` of smoke at a cell, capped at `max`, and dyes the added
+    /// smoke with `color` (each channel between 0 and 1).
+    pub fn add_smoke(&mut self, i: i32, k: i32, amount: f64, max: f64, color: [f64; DYE_CHANNELS]) {
+        let previous = self.s.get(i, k);
+        let next = f64::min(previous + amount, max);
+
+        self.s.set(i, k, next);
+
+        let added = f64::max(next - previous, 0.0);
+        let kept = if previous > 0.0 {
+            (next - added) / previous
+        } else {
+            0.0
+        };
+
+        for (channel, c) in color.iter().enumerate() {
+            self.dye[channel].update(i, k, |d| d * kept + added * c);
+        }
+    }
+
+    fn advect_t(&mut self, i: i32, k: i32) {
+        let (x, y) = self.get_xy_from_grid_indices(i, k, Some(&Field::T));
+
+        let u = self.interpolate(x, y, Field::U);
+        let v = self.interpolate(x, y, Field::V);
+
+        let (previous_x, previous_y) = self.integrate(x, y, (u, v), -self.delta_t);
+
+        let next_val = self.interpolate_with(
+            previous_x,
+            previous_y,
+            Field::T,
+            self.advection_interpolation,
+        );
+
+        self.next_t.set(i, k, next_val);
+    }
+
+    /// Implicit diffusion of the velocity (viscosity) and of the smoke, solved
+    /// with Gauss-Seidel so it stays stable for any coefficient.
+    pub fn diffusion(&mut self) {
+        let scale = self.delta_t / (self.square_size * self.square_size);
+        let iterations = self.diffusion_iterations;
+
+        if self.viscosity > 0.0 {
+            let a = self.viscosity * scale;
+            let b = &self.b;
+
+            self.next_u.copy_from(&self.u);
+            diffuse(
+                &mut self.u,
+                &self.next_u,
+                |i, k| b.get(i - 1, k) != 0 && b.get(i, k) != 0,
+                a,
+                iterations,
+            );
+
+            self.next_v.copy_from(&self.v);
+            diffuse(
+                &mut self.v,
+                &self.next_v,
+                |i, k| b.get(i, k - 1) != 0 && b.get(i, k) != 0,
+                a,
+                iterations,
+            );
+        }
+
+        if self.diffusion > 0.0 {
+            let a = self.diffusion * scale;
+            let b = &self.b;
+
+            self.next_s.copy_from(&self.s);
+            diffuse(
+                &mut self.s,
+                &self.next_s,
+                |i, k| b.get(i, k) != 0,
+                a,
+                iterations,
+            );
+
+            for channel in 0..DYE_CHANNELS {
+                self.next_dye[channel].copy_from(&self.dye[channel]);
+                diffuse(
+                    &mut self.dye[channel],
+                    &self.next_dye[channel],
+                    |i, k| b.get(i, k) != 0,
+                    a,
+                    iterations,
+                );
+            }
+        }
+    }
+
+    /// Boussinesq buoyancy: smoke pulls `v` down and heat pushes it up (the y
+    /// axis points down the page).
+    pub fn apply_buoyancy(&mut self) {
+        if self.smoke_weight == 0.0 && self.thermal_expansion == 0.0 {
+            return;
+        }
+
+        // the border is refilled from the fluid by `apply_boundaries`
+        for (i, k, v) in self.v.interior_mut() {
+            if self.b.get(i, k) == 0 || self.b.get(i, k - 1) == 0 {
+                continue;
+            }
+
+            let s = (self.s.get(i, k) + self.s.get(i, k - 1)) / 2.0;
+            let t = (self.t.get(i, k) + self.t.get(i, k - 1)) / 2.0;
+
+            let force = self.smoke_weight * s - self.thermal_expansion * t;
+
+            *v += force * self.delta_t;
+        }
+    }
+
+    /// Vorticity confinement: adds back the small swirls that advection smooths
+    /// out by spinning the fluid around nearby vorticity peaks.
+    pub fn confine_vorticity(&mut self) {
+        if self.vorticity_confinement == 0.0 {
+            return;
+        }
+
+        for (i, k) in self.w.indices() {
+            let w = if self.b.get(i, k) == 0 {
+                0.0
+            } else {
+                self.get_vorticity(i, k)
+            };
+
+            self.w.set(i, k, w);
+        }
+
+        let scale = self.vorticity_confinement * self.square_size * self.delta_t;
+
+        for (i, k) in self.b.indices() {
+            if self.b.get(i, k) == 0 {
+                continue;
+            }
+
+            let (fx, fy) = self.get_confinement_force(i, k);
+
+            if self.b.get(i - 1, k) != 0 {
+                let (left_fx, _) = self.get_confinement_force(i - 1, k);
+                self.u.update(i, k, |u| u + scale * (fx + left_fx) / 2.0);
+            }
+            if self.b.get(i, k - 1) != 0 {
+                let (_, top_fy) = self.get_confinement_force(i, k - 1);
+                self.v.update(i, k, |v| v + scale * (fy + top_fy) / 2.0);
+            }
+        }
+    }
+
+    fn get_vorticity(&self, i: i32, k: i32) -> f64 {
+        let center_u = |i: i32, k: i32| (self.u.get(i, k) + self.u.get(i + 1, k)) / 2.0;
+        let center_v = |i: i32, k: i32| (self.v.get(i, k) + self.v.get(i, k + 1)) / 2.0;
+
+        let dv_dx = center_v(i + 1, k) - center_v(i - 1, k);
+        let du_dy = center_u(i, k + 1) - center_u(i, k - 1);
+
+        (dv_dx - du_dy) / (2.0 * self.square_size)
+    }
+
+    /// Unscaled confinement force `N x w` at a cell center, where `N` is the
+    /// normalised gradient of the vorticity magnitude.
+    fn get_confinement_force(&self, i: i32, k: i32) -> (f64, f64) {
+        let grad_x = self.w.get(i + 1, k).abs() - self.w.get(i - 1, k).abs();
+        let grad_y = self.w.get(i, k + 1).abs() - self.w.get(i, k - 1).abs();
+
+        let length = f64::sqrt(grad_x * grad_x + grad_y * grad_y);
+
+        if length < 1e-9 {
+            return (0.0, 0.0);
+        }
+
+        let w = self.w.get(i, k);
+
+        (grad_y / length * w, -grad_x / length * w)
     }
 
     pub fn clear(&mut self) {
         self.u.fill(0.0);
         self.v.fill(0.0);
         self.s.fill(0.0);
+        self.p.fill(0.0);
+        self.t.fill(0.0);
+
+        for dye in self.dye.iter_mut() {
+            dye.fill(0.0);
+        }
     }
 
     pub fn resize(&mut self, width: f64, height: f64) {
@@ -233,96 +1530,162 @@ impl Fluid {
             .resize(grid_width + 2 * self.n, grid_height + 2 * self.n);
         self.b
             .resize(grid_width + 2 * self.n, grid_height + 2 * self.n);
+        self.p
+            .resize(grid_width + 2 * self.n, grid_height + 2 * self.n);
+        self.w
+            .resize(grid_width + 2 * self.n, grid_height + 2 * self.n);
+        self.t
+            .resize(grid_width + 2 * self.n, grid_height + 2 * self.n);
 
+        self.solid_u.resize(self.u.width(), self.u.height());
+        self.solid_v.resize(self.v.width(), self.v.height());
+        self.face_u.resize(self.u.width(), self.u.height());
+        self.face_v.resize(self.v.width(), self.v.height());
+        self.painted.resize(self.b.width(), self.b.height());
         self.next_u.resize(self.u.width(), self.u.height());
         self.next_v.resize(self.v.width(), self.v.height());
         self.next_s.resize(self.s.width(), self.s.height());
+        self.next_t.resize(self.t.width(), self.t.height());
+        self.previous_u.resize(self.u.width(), self.u.height());
+        self.previous_v.resize(self.v.width(), self.v.height());
+        self.previous_s.resize(self.s.width(), self.s.height());
+
+        for channel in 0..DYE_CHANNELS {
+            self.dye[channel].resize(self.s.width(), self.s.height());
+            self.next_dye[channel].resize(self.s.width(), self.s.height());
+            self.previous_dye[channel].resize(self.s.width(), self.s.height());
+        }
 
-        self.fill_edges_with_obstacles();
+        self.corrected.resize(self.u.width(), self.v.height());
+
+        self.update_obstacles();
     }
 }
 
 impl FluidSimulation for Fluid {
     fn projection(&mut self) {
-        for _ in 0..self.n_iterations {
-            self.solve_divergence_for_all();
+        self.p.fill(0.0);
+        self.last_iterations = 0;
+
+        let n_iterations = self.n_iterations;
+        let tolerance = self.tolerance;
+        let residual_norm = self.residual_norm;
+        let (solver, mut system) = self.get_pressure_system();
+
+        system.enforce_boundaries();
+        solver.begin(&mut system);
+
+        let mut iterations = 0;
+
+        for _ in 0..n_iterations {
+            solver.iterate(&mut system);
+            iterations += 1;
+
+            if let Some(tolerance) = tolerance
+                && system.get_residual(residual_norm) < tolerance
+            {
+                break;
+            }
         }
+
+        self.last_residual = system.get_residual(residual_norm);
+        self.last_iterations = iterations;
+
+        self.apply_boundaries();
     }
 
     fn advection(&mut self) {
-        for i in 0..self.b.width() {
-            for k in 0..self.b.height() {
-                let i = i as i32;
-                let k = k as i32;
-
-                if self.b.get(i, k) == 0 {
-                    self.next_u.set(i, k, self.u.get(i, k));
-                    self.next_v.set(i, k, self.v.get(i, k));
-                    self.next_s.set(i, k, self.s.get(i, k));
-                    continue;
+        for (i, k) in self.b.indices() {
+            if self.b.get(i, k) == 0 {
+                self.next_u.set(i, k, self.u.get(i, k));
+                self.next_v.set(i, k, self.v.get(i, k));
+                self.next_s.set(i, k, self.s.get(i, k));
+                self.next_t.set(i, k, self.t.get(i, k));
+
+                for channel in 0..DYE_CHANNELS {
+                    self.next_dye[channel].set(i, k, self.dye[channel].get(i, k));
                 }
+                continue;
+            }
 
-                self.advect_u(i, k);
-                self.advect_v(i, k);
-                self.advect_s(i, k);
+            self.advect_u(i, k);
+            self.advect_v(i, k);
+            self.advect_s(i, k);
+            self.advect_t(i, k);
+        }
+
+        if self.advection_scheme != AdvectionScheme::SemiLagrangian {
+            self.correct_advection(Field::U);
+            self.correct_advection(Field::V);
+            self.correct_advection(Field::S);
+            self.correct_advection(Field::T);
+
+            for channel in 0..DYE_CHANNELS {
+                self.correct_advection(Field::Dye(channel));
             }
         }
 
+        self.dissipate();
+
         self.u.swap(&mut self.next_u);
         self.v.swap(&mut self.next_v);
         self.s.swap(&mut self.next_s);
-    }
-
-    fn interpolate(&self, x: f64, y: f64, field: Field) -> f64 {
-        let field_arr = match field {
-            Field::U => &self.u,
-            Field::V => &self.v,
-            Field::S => &self.s,
-        };
+        self.t.swap(&mut self.next_t);
 
-        let (i, k) = self.get_grid_indices_from_xy(x, y, Some(&field));
-        let (grid_x, grid_y) = self.get_xy_from_grid_indices(i, k, Some(&field));
+        for channel in 0..DYE_CHANNELS {
+            self.dye[channel].swap(&mut self.next_dye[channel]);
+        }
 
-        let xx = x - grid_x;
-        let yy = y - grid_y;
+        self.apply_boundaries();
+    }
 
-        let w_x = 1.0 - xx / self.square_size;
-        let w_y = 1.0 - yy / self.square_size;
+    fn get_pressure(&self) -> &Grid<f64> {
+        &self.p
+    }
 
-        let new_value_bot = w_x * field_arr.get(i, k) + (1.0 - w_x) * field_arr.get(i + 1, k);
-        let new_value_top =
-            w_x * field_arr.get(i, k + 1) + (1.0 - w_x) * field_arr.get(i + 1, k + 1);
+    fn interpolate(&self, x: f64, y: f64, field: Field) -> f64 {
+        self.interpolate_with(x, y, field, Interpolation::Bilinear)
+    }
 
-        w_y * new_value_bot + (1.0 - w_y) * new_value_top
+    fn interpolate_with(&self, x: f64, y: f64, field: Field, interpolation: Interpolation) -> f64 {
+        self.interpolate_grid(self.get_field(&field), x, y, &field, interpolation)
     }
 
     fn get_grid_indices_from_xy(&self, x: f64, y: f64, field: Option<&Field>) -> (i32, i32) {
         let i = (x - match field {
-            Some(Field::V | Field::S) => self.square_size / 2.0,
+            Some(Field::V | Field::S | Field::P | Field::T | Field::Dye(_)) => {
+                self.square_size / 2.0
+            }
             _ => 0.0,
         } + self.block_offset)
             / self.square_size;
 
         let k = (y - match field {
-            Some(Field::U | Field::S) => self.square_size / 2.0,
+            Some(Field::U | Field::S | Field::P | Field::T | Field::Dye(_)) => {
+                self.square_size / 2.0
+            }
             _ => 0.0,
         } + self.block_offset)
             / self.square_size;
 
-        (i as i32, k as i32)
+        (i.floor() as i32, k.floor() as i32)
     }
 
     fn get_xy_from_grid_indices(&self, i: i32, k: i32, field: Option<&Field>) -> (f64, f64) {
         let x = i as f64 * self.square_size
             + match field {
-                Some(Field::V | Field::S) => self.square_size / 2.0,
+                Some(Field::V | Field::S | Field::P | Field::T | Field::Dye(_)) => {
+                    self.square_size / 2.0
+                }
                 _ => 0.0,
             }
             - self.block_offset;
 
         let y = k as f64 * self.square_size
             + match field {
-                Some(Field::U | Field::S) => self.square_size / 2.0,
+                Some(Field::U | Field::S | Field::P | Field::T | Field::Dye(_)) => {
+                    self.square_size / 2.0
+                }
                 _ => 0.0,
             }
             - self.block_offset;
@@ -330,3 +1693,249 @@ impl FluidSimulation for Fluid {
         (x, y)
     }
 }
+
+/// Solves `(1 - a * laplacian) x = x0` over the open cells of `x`. Closed
+/// neighbours are left out, so nothing diffuses into or out of obstacles.
+fn diffuse<F: Fn(i32, i32) -> bool>(
+    x: &mut Grid<f64>,
+    x0: &Grid<f64>,
+    is_open: F,
+    a: f64,
+    iterations: usize,
+) {
+    for _ in 0..iterations {
+        for (i, k, &value) in x0.iter() {
+            if !is_open(i, k) {
+                continue;
+            }
+
+            let mut sum = 0.0;
+            let mut n = 0.0;
+
+            for (ii, kk) in [(i - 1, k), (i + 1, k), (i, k - 1), (i, k + 1)] {
+                if x.has(ii, kk) && is_open(ii, kk) {
+                    sum += x.get(ii, kk);
+                    n += 1.0;
+                }
+            }
+
+            x.set(i, k, (value + a * sum) / (1.0 + a * n));
+        }
+    }
+}
+
+/// Copies column `from` of `grid` into column `to`, or row into row when
+/// `rows` is set.
+fn copy_line(grid: &mut Grid<f64>, from: i32, to: i32, rows: bool) {
+    let length = if rows { grid.width() } else { grid.height() };
+
+    for j in 0..length as i32 {
+        let (from, to) = if rows {
+            ((j, from), (j, to))
+        } else {
+            ((from, j), (to, j))
+        };
+
+        grid.set(to.0, to.1, grid.get(from.0, from.1));
+    }
+}
+
+/// Sets columns `a` and `b` of `grid` to their average, or rows when `rows`
+/// is set.
+fn average_lines(grid: &mut Grid<f64>, a: i32, b: i32, rows: bool) {
+    let length = if rows { grid.width() } else { grid.height() };
+
+    for j in 0..length as i32 {
+        let (a, b) = if rows {
+            ((j, a), (j, b))
+        } else {
+            ((a, j), (b, j))
+        };
+
+        let average = (grid.get(a.0, a.1) + grid.get(b.0, b.1)) / 2.0;
+
+        grid.set(a.0, a.1, average);
+        grid.set(b.0, b.1, average);
+    }
+}
+
+/// Multiplies column `line` of `grid` by `factor`, or row when `rows` is set.
+fn scale_line(grid: &mut Grid<f64>, line: i32, factor: f64, rows: bool) {
+    let length = if rows { grid.width() } else { grid.height() };
+
+    for j in 0..length as i32 {
+        let (i, k) = if rows { (j, line) } else { (line, j) };
+
+        grid.update(i, k, |value| value * factor);
+    }
+}
+
+/// One `integrator` step of `delta_t` from `(x, y)`, where the velocity is
+/// `(u, v)`, sampling the velocity anywhere else through `get_velocity`.
+fn integrate_position<F: Fn(f64, f64) -> (f64, f64)>(
+    integrator: Integrator,
+    (x, y): (f64, f64),
+    (u, v): (f64, f64),
+    delta_t: f64,
+    get_velocity: F,
+) -> (f64, f64) {
+    match integrator {
+        Integrator::Euler => (x + u * delta_t, y + v * delta_t),
+        Integrator::Rk2 => {
+            let (mid_u, mid_v) = get_velocity(x + u * delta_t / 2.0, y + v * delta_t / 2.0);
+
+            (x + mid_u * delta_t, y + mid_v * delta_t)
+        }
+        Integrator::Rk3 => {
+            let (u2, v2) = get_velocity(x + u * delta_t / 2.0, y + v * delta_t / 2.0);
+            let (u3, v3) = get_velocity(x + u2 * delta_t * 0.75, y + v2 * delta_t * 0.75);
+
+            (
+                x + (2.0 * u + 3.0 * u2 + 4.0 * u3) / 9.0 * delta_t,
+                y + (2.0 * v + 3.0 * v2 + 4.0 * v3) / 9.0 * delta_t,
+            )
+        }
+    }
+}
+
+/// Catmull-Rom spline through `p0..p3`, evaluated between `p1` (t = 0) and
+/// `p2` (t = 1).
+fn catmull_rom(p0: f64, p1: f64, p2: f64, p3: f64, t: f64) -> f64 {
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t * t
+        + (3.0 * (p1 - p2) + p3 - p0) * t * t * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEP: f64 = 1.0 / 60.0;
+
+    #[test]
+    fn periodic_edges_wrap_in_pairs() {
+        use BoundaryMode::*;
+
+        let boundaries = Boundaries::new(Periodic, Outflow, NoSlip, FreeSlip);
+
+        assert_eq!(
+            boundaries,
+            Boundaries::new(Periodic, Periodic, NoSlip, FreeSlip)
+        );
+        assert_eq!(boundaries.wrap(), Wrap { x: true, y: false });
+
+        let boundaries = Boundaries::new(NoSlip, NoSlip, Outflow, Periodic);
+
+        assert_eq!(boundaries.wrap(), Wrap { x: false, y: true });
+    }
+
+    #[test]
+    fn default_dissipation_matches_original_fade() {
+        let dissipation = Dissipation::default();
+
+        for s in [0.0, 0.05, 0.1, 0.5, 1.0, 3.0] {
+            let expected = f64::max(s / 1.05 - 0.1, 0.0);
+            let faded = dissipation.apply(s, STEP);
+
+            assert!(
+                (faded - expected).abs() < 1e-12,
+                "{s}: {faded} != {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn dissipation_fades_independently_of_step_rate() {
+        let half_life = Dissipation::new(DissipationKind::HalfLife, 2.0);
+        assert!((half_life.apply(1.0, 2.0) - 0.5).abs() < 1e-12);
+
+        let mut value = 1.0;
+        for _ in 0..120 {
+            value = half_life.apply(value, STEP);
+        }
+        assert!((value - 0.5).abs() < 1e-12);
+
+        let linear = Dissipation::new(DissipationKind::Linear, 0.3);
+        assert!((linear.apply(1.0, 1.0) - 0.7).abs() < 1e-12);
+        assert_eq!(linear.apply(0.1, 1.0), 0.0);
+
+        assert_eq!(
+            Dissipation::new(DissipationKind::None, 5.0).apply(0.8, 1.0),
+            0.8
+        );
+    }
+
+    /// Moves `(x, y)` by `delta_t` through the field `(x, -y)`, whose path is
+    /// `(x e^t, y e^-t)`.
+    fn integrate_stretch(integrator: Integrator, delta_t: f64) -> (f64, f64) {
+        let get_velocity = |x: f64, y: f64| (x, -y);
+        integrate_position(
+            integrator,
+            (2.0, 3.0),
+            get_velocity(2.0, 3.0),
+            delta_t,
+            get_velocity,
+        )
+    }
+
+    #[test]
+    fn integrators_match_taylor_series_of_path() {
+        let dt = 0.1;
+        let taylor = |order: i32, dt: f64| -> f64 {
+            (0..=order)
+                .fold((0.0, 1.0), |(sum, term), n| {
+                    (sum + term, term * dt / (n + 1) as f64)
+                })
+                .0
+        };
+
+        for (integrator, order) in [
+            (Integrator::Euler, 1),
+            (Integrator::Rk2, 2),
+            (Integrator::Rk3, 3),
+        ] {
+            let (x, y) = integrate_stretch(integrator, dt);
+
+            assert!(
+                (x - 2.0 * taylor(order, dt)).abs() < 1e-12,
+                "{integrator:?} x = {x}"
+            );
+            assert!(
+                (y - 3.0 * taylor(order, -dt)).abs() < 1e-12,
+                "{integrator:?} y = {y}"
+            );
+        }
+    }
+
+    #[test]
+    fn integrators_follow_uniform_flow() {
+        for integrator in [Integrator::Euler, Integrator::Rk2, Integrator::Rk3] {
+            let (x, y) = integrate_position(integrator, (1.0, 2.0), (3.0, -4.0), -0.5, |_, _| {
+                (3.0, -4.0)
+            });
+
+            assert!(
+                (x + 0.5).abs() < 1e-12 && (y - 4.0).abs() < 1e-12,
+                "{integrator:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_middle_samples() {
+        assert_eq!(catmull_rom(4.0, -1.0, 2.5, 7.0, 0.0), -1.0);
+        assert_eq!(catmull_rom(4.0, -1.0, 2.5, 7.0, 1.0), 2.5);
+    }
+
+    #[test]
+    fn catmull_rom_reproduces_quadratics() {
+        let curve = |x: f64| 0.5 * x * x - 2.0 * x + 3.0;
+
+        for t in [0.1, 0.25, 0.5, 0.9] {
+            let value = catmull_rom(curve(-1.0), curve(0.0), curve(1.0), curve(2.0), t);
+
+            assert!((value - curve(t)).abs() < 1e-12, "{t}: {value}");
+        }
+    }
+}
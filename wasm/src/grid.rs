@@ -1,36 +1,46 @@
 use std::fmt::{Debug, Formatter};
 
+/// A 2D grid stored column by column in a single `Vec`, so that `(i, k)` lives
+/// at `i * height + k`.
 #[derive(Clone)]
 pub struct Grid<T> {
-    grid: Vec<Vec<T>>,
+    data: Vec<T>,
+    width: usize,
+    height: usize, // stride between columns
 }
 
 impl<T: Default + Copy> Grid<T> {
     pub fn new(width: usize, height: usize) -> Grid<T> {
-        let grid = vec![vec![T::default(); height]; width];
+        let data = vec![T::default(); width * height];
 
-        Grid { grid }
+        Grid {
+            data,
+            width,
+            height,
+        }
     }
 
     pub fn width(&self) -> usize {
-        self.grid.len()
+        self.width
     }
 
     pub fn height(&self) -> usize {
-        self.grid[0].len()
+        self.height
     }
 
     pub fn has(&self, i: i32, k: i32) -> bool {
-        i >= 0 && i < self.grid.len() as i32 && k >= 0 && k < self.grid[0].len() as i32
+        i >= 0 && i < self.width as i32 && k >= 0 && k < self.height as i32
+    }
+
+    fn index(&self, i: usize, k: usize) -> usize {
+        i * self.height + k
     }
 
     pub fn get(&self, i: i32, k: i32) -> T {
         if !self.has(i, k) {
             T::default()
         } else {
-            let i = i as usize;
-            let k = k as usize;
-            self.grid[i][k]
+            self.data[self.index(i as usize, k as usize)]
         }
     }
 
@@ -38,55 +48,319 @@ impl<T: Default + Copy> Grid<T> {
         if !self.has(i, k) {
             return;
         }
-        let i = i as usize;
-        let k = k as usize;
+        let index = self.index(i as usize, k as usize);
 
-        self.grid[i][k] = value;
+        self.data[index] = value;
     }
 
     pub fn update<F: Fn(T) -> T>(&mut self, i: i32, k: i32, f: F) {
         if !self.has(i, k) {
             return;
         }
-        let i = i as usize;
-        let k = k as usize;
+        let index = self.index(i as usize, k as usize);
 
-        let value = f(self.grid[i][k]);
+        self.data[index] = f(self.data[index]);
+    }
+
+    /// Reads a cell without checking that it is in the grid.
+    ///
+    /// # Safety
+    ///
+    /// `i` must be less than the width and `k` less than the height.
+    pub unsafe fn get_unchecked(&self, i: usize, k: usize) -> T {
+        debug_assert!(i < self.width && k < self.height);
 
-        self.grid[i][k] = value;
+        // SAFETY: the caller guarantees the index is within `data`
+        unsafe { *self.data.get_unchecked(self.index(i, k)) }
     }
 
-    pub fn fill(&mut self, value: T) {
-        for i in 0..self.grid.len() {
-            for k in 0..self.grid[0].len() {
-                self.grid[i][k] = value;
-            }
+    /// Writes a cell without checking that it is in the grid.
+    ///
+    /// # Safety
+    ///
+    /// `i` must be less than the width and `k` less than the height.
+    pub unsafe fn set_unchecked(&mut self, i: usize, k: usize, value: T) {
+        debug_assert!(i < self.width && k < self.height);
+        let index = self.index(i, k);
+
+        // SAFETY: the caller guarantees the index is within `data`
+        unsafe { *self.data.get_unchecked_mut(index) = value };
+    }
+
+    /// Every cell as `(i, k, value)`, column by column.
+    pub fn iter(&self) -> impl Iterator<Item = (i32, i32, &T)> {
+        self.data
+            .chunks(self.height.max(1))
+            .zip(0..)
+            .flat_map(|(column, i)| column.iter().zip(0..).map(move |(value, k)| (i, k, value)))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (i32, i32, &mut T)> {
+        self.data
+            .chunks_mut(self.height.max(1))
+            .zip(0..)
+            .flat_map(|(column, i)| {
+                column
+                    .iter_mut()
+                    .zip(0..)
+                    .map(move |(value, k)| (i, k, value))
+            })
+    }
+
+    /// Like `iter`, without the one cell wide border.
+    pub fn interior(&self) -> impl Iterator<Item = (i32, i32, &T)> {
+        let rows = 1..self.height.saturating_sub(1).max(1);
+        let columns = self.data.chunks(self.height.max(1)).zip(0..);
+
+        columns
+            .skip(1)
+            .take(self.width.saturating_sub(2))
+            .flat_map(move |(column, i)| {
+                column[rows.clone()]
+                    .iter()
+                    .zip(1..)
+                    .map(move |(value, k)| (i, k, value))
+            })
+    }
+
+    pub fn interior_mut(&mut self) -> impl Iterator<Item = (i32, i32, &mut T)> {
+        let rows = 1..self.height.saturating_sub(1).max(1);
+        let columns = self.data.chunks_mut(self.height.max(1)).zip(0..);
+
+        columns
+            .skip(1)
+            .take(self.width.saturating_sub(2))
+            .flat_map(move |(column, i)| {
+                column[rows.clone()]
+                    .iter_mut()
+                    .zip(1..)
+                    .map(move |(value, k)| (i, k, value))
+            })
+    }
+
+    /// Replaces every cell with `f` of its value.
+    #[cfg(any(test, feature = "bench"))]
+    pub fn map<F: Fn(T) -> T>(&mut self, f: F) {
+        for value in self.data.iter_mut() {
+            *value = f(*value);
+        }
+    }
+
+    /// Every `(i, k)` of the grid, column by column. Unlike `iter`, it does
+    /// not borrow the grid, so the cells can be written while iterating.
+    pub fn indices(&self) -> impl DoubleEndedIterator<Item = (i32, i32)> + use<T> {
+        self.region((0, 0), (self.width as i32 - 1, self.height as i32 - 1))
+    }
+
+    /// Every `(i, k)` from `min` to `max` inclusive that is in the grid.
+    pub fn region(
+        &self,
+        min: (i32, i32),
+        max: (i32, i32),
+    ) -> impl DoubleEndedIterator<Item = (i32, i32)> + use<T> {
+        let columns = min.0.max(0)..=max.0.min(self.width as i32 - 1);
+        let rows = min.1.max(0)..=max.1.min(self.height as i32 - 1);
+
+        columns.flat_map(move |i| rows.clone().map(move |k| (i, k)))
+    }
+
+    /// Replaces every cell with `f` of its value and the value at the same
+    /// place in `other`, which must be the same size.
+    pub fn zip_with<U: Copy, F: Fn(T, U) -> T>(&mut self, other: &Grid<U>, f: F) {
+        assert!(self.width == other.width && self.height == other.height);
+
+        for (value, &other) in self.data.iter_mut().zip(other.data.iter()) {
+            *value = f(*value, other);
         }
     }
 
+    pub fn fill(&mut self, value: T) {
+        self.data.fill(value);
+    }
+
+    /// Makes this grid a copy of `other`, reusing its allocation when the
+    /// sizes match.
+    pub fn copy_from(&mut self, other: &Grid<T>) {
+        self.data.clone_from(&other.data);
+        self.width = other.width;
+        self.height = other.height;
+    }
+
     pub fn swap(&mut self, other: &mut Grid<T>) {
-        std::mem::swap(&mut self.grid, &mut other.grid);
+        std::mem::swap(self, other);
     }
 
     pub fn resize(&mut self, width: usize, height: usize) {
-        self.grid.resize(width, vec![T::default(); self.height()]);
+        if height != self.height {
+            let mut data = vec![T::default(); self.width * height];
+            let rows = height.min(self.height);
 
-        for i in 0..self.grid.len() {
-            self.grid[i].resize(height, T::default());
+            for i in 0..self.width {
+                let from = i * self.height;
+                let to = i * height;
+                data[to..to + rows].copy_from_slice(&self.data[from..from + rows]);
+            }
+
+            self.data = data;
+            self.height = height;
         }
+
+        self.data.resize(width * height, T::default());
+        self.width = width;
     }
 }
 
 impl<T: Debug> Debug for Grid<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "[")?;
-        for k in 0..self.grid[0].len() {
+        for k in 0..self.height {
             write!(f, "[")?;
-            for i in 0..self.grid.len() {
-                write!(f, "{:?},", self.grid[i][k])?;
+            for i in 0..self.width {
+                write!(f, "{:?},", self.data[i * self.height + k])?;
             }
             writeln!(f, "]")?;
         }
         write!(f, "]")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The nested `Vec` layout the grid used to have, indexed `[i][k]`.
+    struct Nested(Vec<Vec<i32>>);
+
+    impl Nested {
+        fn new(width: usize, height: usize) -> Nested {
+            Nested(vec![vec![0; height]; width])
+        }
+
+        fn get(&self, i: i32, k: i32) -> i32 {
+            if i < 0 || k < 0 {
+                return 0;
+            }
+            self.0
+                .get(i as usize)
+                .and_then(|column| column.get(k as usize))
+                .copied()
+                .unwrap_or_default()
+        }
+
+        fn set(&mut self, i: i32, k: i32, value: i32) {
+            if i < 0 || k < 0 {
+                return;
+            }
+            if let Some(cell) = self
+                .0
+                .get_mut(i as usize)
+                .and_then(|column| column.get_mut(k as usize))
+            {
+                *cell = value;
+            }
+        }
+
+        fn resize(&mut self, width: usize, height: usize) {
+            self.0.resize(width, vec![0; height]);
+            for column in self.0.iter_mut() {
+                column.resize(height, 0);
+            }
+        }
+    }
+
+    /// Every index of a `width` by `height` grid and two cells around it.
+    fn indices(width: usize, height: usize) -> impl Iterator<Item = (i32, i32)> {
+        (-2..width as i32 + 2).flat_map(move |i| (-2..height as i32 + 2).map(move |k| (i, k)))
+    }
+
+    fn assert_same(grid: &Grid<i32>, nested: &Nested) {
+        for (i, k) in indices(grid.width() + 1, grid.height() + 1) {
+            assert_eq!(grid.get(i, k), nested.get(i, k), "at ({i}, {k})");
+        }
+    }
+
+    #[test]
+    fn get_set_update_match_nested_layout() {
+        let (width, height) = (5, 3);
+        let mut grid = Grid::new(width, height);
+        let mut nested = Nested::new(width, height);
+
+        // writes past the end of a column must not land in the next one
+        for (n, (i, k)) in indices(width, height).enumerate() {
+            grid.set(i, k, n as i32 + 1);
+            nested.set(i, k, n as i32 + 1);
+        }
+        assert_same(&grid, &nested);
+
+        for (i, k) in indices(width, height) {
+            grid.update(i, k, |value| value * 3 - 1);
+            let value = nested.get(i, k);
+            nested.set(i, k, value * 3 - 1);
+        }
+        assert_same(&grid, &nested);
+    }
+
+    #[test]
+    fn resize_keeps_cells_in_place() {
+        let mut grid = Grid::new(4, 4);
+        let mut nested = Nested::new(4, 4);
+
+        for (n, (i, k)) in indices(4, 4).enumerate() {
+            grid.set(i, k, n as i32 + 1);
+            nested.set(i, k, n as i32 + 1);
+        }
+
+        for (width, height) in [(6, 4), (6, 7), (3, 7), (5, 2), (1, 1), (4, 5)] {
+            grid.resize(width, height);
+            nested.resize(width, height);
+            assert_eq!((grid.width(), grid.height()), (width, height));
+            assert_same(&grid, &nested);
+        }
+    }
+
+    #[test]
+    fn iterators_visit_cells_column_by_column() {
+        let mut grid = Grid::new(4, 3);
+
+        for (i, k, value) in grid.iter_mut() {
+            *value = i * 10 + k;
+        }
+
+        let cells: Vec<_> = grid.iter().map(|(i, k, &value)| (i, k, value)).collect();
+        let expected: Vec<_> = (0..4)
+            .flat_map(|i| (0..3).map(move |k| (i, k, i * 10 + k)))
+            .collect();
+        assert_eq!(cells, expected);
+
+        let interior: Vec<_> = grid
+            .interior()
+            .map(|(i, k, &value)| (i, k, value))
+            .collect();
+        assert_eq!(interior, [(1, 1, 11), (2, 1, 21)]);
+
+        let indices: Vec<_> = grid.indices().collect();
+        let expected: Vec<_> = expected.iter().map(|&(i, k, _)| (i, k)).collect();
+        assert_eq!(indices, expected);
+
+        let region: Vec<_> = grid.region((-1, 1), (1, 5)).collect();
+        assert_eq!(region, [(0, 1), (0, 2), (1, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn combinators_update_cells_in_place() {
+        let mut grid = Grid::new(4, 3);
+        grid.fill(2);
+
+        for (_, _, value) in grid.interior_mut() {
+            *value = 5;
+        }
+        grid.map(|value| value * 10);
+
+        let mut other = Grid::new(4, 3);
+        other.set(1, 1, 7);
+        grid.zip_with(&other, |value, other| value + other);
+
+        let values: Vec<_> = grid.iter().map(|(_, _, &value)| value).collect();
+        assert_eq!(values, [20, 20, 20, 20, 57, 20, 20, 50, 20, 20, 20, 20]);
+    }
+}
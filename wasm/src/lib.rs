@@ -1,15 +1,35 @@
+#[cfg(any(test, feature = "bench"))]
+mod fixture;
 mod fluid;
 mod grid;
+mod multigrid;
+mod obstacle;
+mod pressure;
 mod scene;
 mod util;
 
-use self::fluid::FluidSimulation;
-use self::scene::Scene;
+use self::fluid::{
+    AdvectionScheme, Boundaries, BoundaryMode, Dissipation, DissipationKind, FluidSimulation,
+    Integrator, Interpolation, ResidualNorm,
+};
+use self::obstacle::{Obstacle, Shape};
+use self::pressure::{PressureSolverKind, SweepOrder};
+use self::scene::{PointerMode, Scene};
 use self::util::is_mobile;
 use std::cell::RefCell;
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 
+/// Internals the benches drive directly, behind the `bench` feature.
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench {
+    pub use crate::fixture::Domain;
+    pub use crate::fluid::ResidualNorm;
+    pub use crate::grid::Grid;
+    pub use crate::pressure::{PressureSolverKind, new_pressure_solver};
+}
+
 thread_local! {
     static SCENE: Rc<RefCell<Option<Scene>>> = Rc::new(RefCell::new(None));
 }
@@ -80,6 +100,17 @@ pub fn toggle_playing() {
     });
 }
 
+#[wasm_bindgen]
+pub fn toggle_show_pressure() {
+    SCENE.with(move |scene| {
+        if scene.borrow().is_none() {
+            return;
+        }
+
+        scene.borrow_mut().as_mut().unwrap().toggle_show_pressure();
+    });
+}
+
 #[wasm_bindgen]
 pub fn stop() {
     if is_mobile() {
@@ -165,6 +196,22 @@ pub struct FpsStats {
     pub average_fps: f64,
     pub resolution: usize,
     pub subdivisions: u8,
+    pub iterations: usize,
+    pub residual: f64,
+    pub substeps: usize,
+}
+
+impl FpsStats {
+    fn new(scene: &Scene) -> FpsStats {
+        FpsStats {
+            average_fps: scene.get_average_fps().min(1.0 / scene.fluid.delta_t),
+            resolution: scene.fluid.max_squares,
+            subdivisions: scene.subdivisions,
+            iterations: scene.fluid.last_iterations,
+            residual: scene.fluid.last_residual,
+            substeps: scene.fluid.last_substeps,
+        }
+    }
 }
 
 #[wasm_bindgen]
@@ -172,14 +219,10 @@ pub fn get_stats() -> Option<FpsStats> {
     let mut stats = None;
 
     SCENE.with(|scene| {
-        if let Ok(scene) = scene.try_borrow() {
-            if let Some(scene) = scene.as_ref() {
-                stats = Some(FpsStats {
-                    average_fps: scene.get_average_fps().min(1.0 / scene.fluid.delta_t),
-                    resolution: scene.fluid.max_squares,
-                    subdivisions: scene.subdivisions,
-                });
-            }
+        if let Ok(scene) = scene.try_borrow()
+            && let Some(scene) = scene.as_ref()
+        {
+            stats = Some(FpsStats::new(scene));
         }
     });
 
@@ -189,15 +232,15 @@ pub fn get_stats() -> Option<FpsStats> {
 #[wasm_bindgen]
 pub fn set_stats(resolution: usize, subdivisions: u8) {
     SCENE.with(|scene| {
-        if let Ok(scene) = scene.try_borrow_mut().as_mut() {
-            if let Some(scene) = scene.as_mut() {
-                scene.subdivisions = subdivisions;
-                scene.fluid.max_squares = resolution;
-                scene.mouse_radius = resolution as i32 / 20;
-                scene
-                    .fluid
-                    .resize(scene.canvas.width() as f64, scene.canvas.height() as f64);
-            }
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.subdivisions = subdivisions;
+            scene.fluid.max_squares = resolution;
+            scene.mouse_radius = resolution as i32 / 20;
+            scene
+                .fluid
+                .resize(scene.canvas.width() as f64, scene.canvas.height() as f64);
         }
     })
 }
@@ -207,18 +250,360 @@ pub fn adjust_to_device_performance() -> Option<FpsStats> {
     let mut stats = None;
 
     SCENE.with(|scene| {
-        if let Ok(scene) = scene.try_borrow_mut().as_mut() {
-            if let Some(scene) = scene.as_mut() {
-                scene.adjust_to_device_performance();
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.adjust_to_device_performance();
 
-                stats = Some(FpsStats {
-                    average_fps: scene.get_average_fps().min(1.0 / scene.fluid.delta_t),
-                    resolution: scene.fluid.max_squares,
-                    subdivisions: scene.subdivisions,
-                });
-            }
+            stats = Some(FpsStats::new(scene));
         }
     });
 
     stats
 }
+
+#[wasm_bindgen]
+pub fn set_tolerance(tolerance: Option<f64>, residual_norm: ResidualNorm) {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.fluid.tolerance = tolerance;
+            scene.fluid.residual_norm = residual_norm;
+        }
+    })
+}
+
+#[wasm_bindgen]
+pub fn set_pressure_solver(kind: PressureSolverKind) {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.fluid.set_pressure_solver(kind);
+        }
+    })
+}
+
+#[wasm_bindgen]
+pub fn set_relaxation(overrelaxation_coefficient: f64, jacobi_weight: f64) {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.fluid.overrelaxation_coefficient = overrelaxation_coefficient;
+            scene.fluid.jacobi_weight = jacobi_weight;
+        }
+    })
+}
+
+#[wasm_bindgen]
+pub fn set_sweep_order(order: SweepOrder) {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.fluid.sweep_order = order;
+        }
+    })
+}
+
+#[wasm_bindgen]
+pub fn set_vorticity_confinement(epsilon: f64) {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.fluid.vorticity_confinement = epsilon;
+        }
+    })
+}
+
+#[wasm_bindgen]
+pub fn set_diffusion(viscosity: f64, diffusion: f64) {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.fluid.viscosity = viscosity;
+            scene.fluid.diffusion = diffusion;
+        }
+    })
+}
+
+#[wasm_bindgen]
+pub fn set_buoyancy(smoke_weight: f64, thermal_expansion: f64) {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.fluid.smoke_weight = smoke_weight;
+            scene.fluid.thermal_expansion = thermal_expansion;
+        }
+    })
+}
+
+#[wasm_bindgen]
+pub fn set_dye_color(r: u8, g: u8, b: u8) {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.set_dye_color([r as f64 / 255.0, g as f64 / 255.0, b as f64 / 255.0]);
+        }
+    })
+}
+
+#[wasm_bindgen]
+pub fn cycle_dye_hue(degrees_per_second: f64) {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.cycle_dye_hue(degrees_per_second);
+        }
+    })
+}
+
+#[wasm_bindgen]
+pub fn set_smoke_dissipation(kind: DissipationKind, value: f64) {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.fluid.smoke_dissipation = Dissipation::new(kind, value);
+        }
+    })
+}
+
+#[wasm_bindgen]
+pub fn set_temperature_dissipation(kind: DissipationKind, value: f64) {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.fluid.temperature_dissipation = Dissipation::new(kind, value);
+        }
+    })
+}
+
+#[wasm_bindgen]
+pub fn set_advection_scheme(scheme: AdvectionScheme) {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.fluid.advection_scheme = scheme;
+        }
+    })
+}
+
+#[wasm_bindgen]
+pub fn set_integrator(integrator: Integrator) {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.fluid.integrator = integrator;
+        }
+    })
+}
+
+#[wasm_bindgen]
+pub fn set_interpolation(advection: Interpolation, rendering: Interpolation) {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.fluid.advection_interpolation = advection;
+            scene.interpolation = rendering;
+        }
+    })
+}
+
+#[wasm_bindgen]
+pub fn set_boundaries(
+    left: BoundaryMode,
+    right: BoundaryMode,
+    top: BoundaryMode,
+    bottom: BoundaryMode,
+) {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene
+                .fluid
+                .set_boundaries(Boundaries::new(left, right, top, bottom));
+        }
+    })
+}
+
+#[wasm_bindgen]
+pub fn set_wind_tunnel(speed: Option<f64>, streaks: usize) {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.set_wind_tunnel(speed, streaks);
+        }
+    })
+}
+
+fn add_obstacle(obstacle: Obstacle) -> Option<u32> {
+    let mut id = None;
+
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            id = Some(scene.fluid.add_obstacle(obstacle));
+        }
+    });
+
+    id
+}
+
+#[wasm_bindgen]
+pub fn add_circle(x: f64, y: f64, radius: f64) -> Option<u32> {
+    add_obstacle(Obstacle::new(x, y, 0.0, Shape::Circle { radius }))
+}
+
+/// `x` and `y` are the center of the rectangle, `angle` is in radians.
+#[wasm_bindgen]
+pub fn add_rectangle(x: f64, y: f64, width: f64, height: f64, angle: f64) -> Option<u32> {
+    add_obstacle(Obstacle::new(
+        x,
+        y,
+        angle,
+        Shape::Rectangle { width, height },
+    ))
+}
+
+/// `points` holds the vertices as `[x0, y0, x1, y1, ...]`.
+#[wasm_bindgen]
+pub fn add_polygon(points: Vec<f64>) -> Option<u32> {
+    let points: Vec<(f64, f64)> = points.chunks_exact(2).map(|p| (p[0], p[1])).collect();
+
+    if points.len() < 3 {
+        return None;
+    }
+
+    add_obstacle(Obstacle::polygon(&points))
+}
+
+/// Polygons are positioned at the average of their vertices.
+#[wasm_bindgen]
+pub fn move_obstacle(id: u32, x: f64, y: f64, angle: f64) -> bool {
+    let mut moved = false;
+
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            moved = scene.fluid.move_obstacle(id, x, y, angle);
+        }
+    });
+
+    moved
+}
+
+#[wasm_bindgen]
+pub fn remove_obstacle(id: u32) -> bool {
+    let mut removed = false;
+
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            removed = scene.fluid.remove_obstacle(id);
+        }
+    });
+
+    removed
+}
+
+#[wasm_bindgen]
+pub fn clear_obstacles() {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.clear_obstacles();
+        }
+    })
+}
+
+#[wasm_bindgen]
+pub fn set_obstacle_velocity(id: u32, u: f64, v: f64, angular_velocity: f64) -> bool {
+    let mut found = false;
+
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            found = scene
+                .fluid
+                .set_obstacle_velocity(id, (u, v), angular_velocity);
+        }
+    });
+
+    found
+}
+
+#[wasm_bindgen]
+pub fn set_paddle(radius: Option<f64>) {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.set_paddle(radius);
+        }
+    })
+}
+
+#[wasm_bindgen]
+pub fn set_dom_obstacles(selectors: Vec<String>) {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.set_dom_obstacles(selectors);
+        }
+    })
+}
+
+#[wasm_bindgen]
+pub fn set_pointer_mode(mode: PointerMode) {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.set_pointer_mode(mode);
+        }
+    })
+}
+
+#[wasm_bindgen]
+pub fn clear_painted_obstacles() {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.fluid.painted.fill(0);
+            scene.fluid.update_obstacles();
+        }
+    })
+}
+
+#[wasm_bindgen]
+pub fn set_cfl_limit(cfl_limit: Option<f64>, max_substeps: usize) {
+    SCENE.with(|scene| {
+        if let Ok(scene) = scene.try_borrow_mut().as_mut()
+            && let Some(scene) = scene.as_mut()
+        {
+            scene.fluid.cfl_limit = cfl_limit;
+            scene.fluid.max_substeps = max_substeps;
+        }
+    })
+}
@@ -0,0 +1,432 @@
+use crate::grid::Grid;
+use crate::pressure::{
+    PressureSolver, PressureSystem, Wrap, apply_laplacian, is_unknown, unknowns,
+};
+
+#[derive(Debug, Clone)]
+struct Level {
+    b: Grid<u8>,        // fluid cells of this level, with the same border as the finest
+    face_u: Grid<f64>,  // open fraction of the faces between horizontal neighbours
+    face_v: Grid<f64>,  // open fraction of the faces between vertical neighbours
+    x: Grid<f64>,       // correction
+    rhs: Grid<f64>,     // right hand side
+    r: Grid<f64>,       // residual
+    columns: Vec<Span>, // finest columns each column covers
+    rows: Vec<Span>,    // finest rows each row covers
+    wrap: Wrap,
+}
+
+/// First and last finest cell a coarse cell covers along an axis.
+type Span = (i32, i32);
+
+impl Level {
+    fn new(columns: Vec<Span>, rows: Vec<Span>, wrap: Wrap) -> Level {
+        let (width, height) = (columns.len(), rows.len());
+
+        Level {
+            b: Grid::new(width, height),
+            face_u: Grid::new(width + 1, height),
+            face_v: Grid::new(width, height + 1),
+            x: Grid::new(width, height),
+            rhs: Grid::new(width, height),
+            r: Grid::new(width, height),
+            columns,
+            rows,
+            wrap,
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.b.width()
+    }
+
+    fn height(&self) -> usize {
+        self.b.height()
+    }
+
+    fn smooth(&mut self, iterations: usize, reverse: bool) {
+        let width = self.width();
+        let height = self.height();
+
+        for _ in 0..iterations {
+            for i in 1..width - 1 {
+                for k in 1..height - 1 {
+                    let (i, k) = if reverse {
+                        (width - 1 - i, height - 1 - k)
+                    } else {
+                        (i, k)
+                    };
+
+                    // SAFETY: every grid of a level is sized from its width and
+                    // height, and the loops skip the border, so the cell and
+                    // its neighbours and faces are all in range
+                    unsafe {
+                        if self.b.get_unchecked(i, k) == 0 {
+                            continue;
+                        }
+
+                        let w0 = self.face_u.get_unchecked(i, k);
+                        let w1 = self.face_u.get_unchecked(i + 1, k);
+                        let w2 = self.face_v.get_unchecked(i, k);
+                        let w3 = self.face_v.get_unchecked(i, k + 1);
+                        let n = w0 + w1 + w2 + w3;
+
+                        if n == 0.0 {
+                            continue;
+                        }
+
+                        let sum = self.rhs.get_unchecked(i, k)
+                            + w0 * self.x.get_unchecked(i - 1, k)
+                            + w1 * self.x.get_unchecked(i + 1, k)
+                            + w2 * self.x.get_unchecked(i, k - 1)
+                            + w3 * self.x.get_unchecked(i, k + 1);
+
+                        self.x.set_unchecked(i, k, sum / n);
+                    }
+                }
+            }
+
+            self.wrap.fill_ghosts(&mut self.x);
+        }
+    }
+
+    fn compute_residual(&mut self) {
+        for (i, k, r) in self.r.iter_mut() {
+            *r = if !is_unknown(&self.b, i, k) {
+                0.0
+            } else {
+                self.rhs.get(i, k)
+                    - apply_laplacian(&self.face_u, &self.face_v, self.wrap, &self.x, i, k)
+            };
+        }
+    }
+}
+
+/// Geometric multigrid V-cycle on the pressure Poisson equation.
+///
+/// Each iteration is one V-cycle: the current divergence is the right hand
+/// side, smoothed with Gauss-Seidel on successively coarser copies of the
+/// obstacle mask, and the resulting pressure is applied to `u`/`v`.
+#[derive(Debug, Clone)]
+pub struct Multigrid {
+    levels: Vec<Level>,
+    wrap: Wrap, // the levels were built for
+    pub pre_smoothing: usize,
+    pub post_smoothing: usize,
+    pub coarsest_iterations: usize,
+}
+
+impl Multigrid {
+    pub fn new(width: usize, height: usize) -> Multigrid {
+        let mut multigrid = Multigrid {
+            levels: vec![],
+            wrap: Wrap::default(),
+            pre_smoothing: 2,
+            post_smoothing: 2,
+            coarsest_iterations: 20,
+        };
+
+        multigrid.build_levels(width, height);
+
+        multigrid
+    }
+
+    fn build_levels(&mut self, width: usize, height: usize) {
+        self.levels.clear();
+
+        let wrap = self.wrap;
+        let mut columns: Vec<Span> = (0..width as i32).map(|i| (i, i)).collect();
+        let mut rows: Vec<Span> = (0..height as i32).map(|k| (k, k)).collect();
+
+        loop {
+            let (width, height) = (columns.len(), rows.len());
+
+            self.levels
+                .push(Level::new(columns.clone(), rows.clone(), wrap));
+
+            // down to a single interior cell, so that the coarsest level is
+            // solved exactly whatever holds its pressure
+            if Self::coarsen(width) == width && Self::coarsen(height) == height {
+                break;
+            }
+
+            columns = Self::coarsen_spans(&columns, wrap.x);
+            rows = Self::coarsen_spans(&rows, wrap.y);
+        }
+    }
+
+    fn coarsen_spans(spans: &[Span], wrapped: bool) -> Vec<Span> {
+        let mut coarse: Vec<Span> = (0..Self::coarsen(spans.len()) as i32)
+            .map(|index| {
+                let children = Self::children(index, spans.len());
+                (
+                    spans[*children.start() as usize].0,
+                    spans[*children.end() as usize].1,
+                )
+            })
+            .collect();
+
+        // the ghosts of a wrapped axis lie where the cells they stand for
+        // would be, one period over
+        if wrapped {
+            let last = coarse.len() - 1;
+            let period = coarse[last - 1].1 - coarse[1].0 + 1;
+            let shift = |(first, last): Span, by: i32| (first + by, last + by);
+
+            coarse[0] = shift(coarse[last - 1], -period);
+            coarse[last] = shift(coarse[1], period);
+        }
+
+        coarse
+    }
+
+    /// Center of a cell along an axis, in finest cells.
+    ///
+    /// The border keeps the width of a finest cell on every level, so coarse
+    /// centers are not evenly spaced next to it.
+    fn center(spans: &[Span], index: i32) -> f64 {
+        let (first, last) = spans[index as usize];
+        (first + last) as f64 / 2.0
+    }
+
+    /// Halves the interior of a level, keeping its one cell wide border so
+    /// that open and closed edges stay where they are on every level.
+    fn coarsen(size: usize) -> usize {
+        (size - 2).div_ceil(2) + 2
+    }
+
+    /// Fine indices covered by a coarse index along an axis of `fine_size`.
+    fn children(index: i32, fine_size: usize) -> std::ops::RangeInclusive<i32> {
+        let last = fine_size as i32 - 1;
+
+        if index == 0 {
+            0..=0
+        } else if 2 * index > last {
+            last..=last
+        } else {
+            2 * index - 1..=i32::min(2 * index, last - 1)
+        }
+    }
+
+    /// Coarse index covering a fine one along an axis of `fine_size`.
+    fn parent(index: i32, fine_size: usize) -> i32 {
+        if index == 0 {
+            0
+        } else if index == fine_size as i32 - 1 {
+            Self::coarsen(fine_size) as i32 - 1
+        } else {
+            (index - 1) / 2 + 1
+        }
+    }
+
+    /// A coarse cell is fluid as soon as one of its children is.
+    fn restrict_mask(fine: &Grid<u8>, coarse: &mut Grid<u8>) {
+        for (i, k, fluid) in coarse.iter_mut() {
+            *fluid = 0;
+
+            for ii in Self::children(i, fine.width()) {
+                for kk in Self::children(k, fine.height()) {
+                    *fluid |= fine.get(ii, kk);
+                }
+            }
+        }
+    }
+
+    /// A coarse face lets through what the fine faces it is made of let
+    /// through, over the distance between the centers of the cells it joins.
+    ///
+    /// Between two interior cells that is the average of the fine faces, but a
+    /// border held at zero pressure is closer than a coarse cell away, so the
+    /// faces next to it couple more strongly.
+    fn restrict_faces(fine: &Level, coarse: &mut Level) {
+        let fine_width = fine.width();
+        let fine_height = fine.height();
+
+        // the first fine face of the coarse cell, or the far edge past the last
+        let first_face = |index: i32, coarse_size: usize, fine_size: usize| {
+            if index as usize == coarse_size {
+                fine_size as i32
+            } else {
+                *Self::children(index, fine_size).start()
+            }
+        };
+
+        // distance between the centers of the cells a face joins
+        let spacing = |spans: &[Span], index: i32| {
+            (index > 0 && (index as usize) < spans.len())
+                .then(|| Self::center(spans, index) - Self::center(spans, index - 1))
+        };
+
+        // the outer faces join no two cells and keep the plain average
+        let scale = |fine_spans: &[Span], coarse_spans: &[Span], ii, i, count: usize| match (
+            spacing(fine_spans, ii),
+            spacing(coarse_spans, i),
+        ) {
+            (Some(fine), Some(coarse)) => fine / coarse,
+            _ => 1.0 / count as f64,
+        };
+
+        let (coarse_width, coarse_height) = (coarse.width(), coarse.height());
+
+        for (i, k, open) in coarse.face_u.iter_mut() {
+            let ii = first_face(i, coarse_width, fine_width);
+            let rows = Self::children(k, fine_height);
+            let count = rows.clone().count();
+
+            *open = rows.map(|kk| fine.face_u.get(ii, kk)).sum::<f64>()
+                * scale(&fine.columns, &coarse.columns, ii, i, count);
+        }
+
+        for (i, k, open) in coarse.face_v.iter_mut() {
+            let kk = first_face(k, coarse_height, fine_height);
+            let columns = Self::children(i, fine_width);
+            let count = columns.clone().count();
+
+            *open = columns.map(|ii| fine.face_v.get(ii, kk)).sum::<f64>()
+                * scale(&fine.rows, &coarse.rows, kk, k, count);
+        }
+    }
+
+    /// Sums the residual of the children into the coarse right hand side.
+    ///
+    /// The coarse cells are twice as wide, so the sum (four times the average)
+    /// is what keeps the unscaled Poisson matrix consistent between levels.
+    fn restrict(fine_r: &Grid<f64>, fine_b: &Grid<u8>, coarse_rhs: &mut Grid<f64>) {
+        for (i, k, sum) in coarse_rhs.iter_mut() {
+            *sum = 0.0;
+
+            for ii in Self::children(i, fine_b.width()) {
+                for kk in Self::children(k, fine_b.height()) {
+                    if is_unknown(fine_b, ii, kk) {
+                        *sum += fine_r.get(ii, kk);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds the bilinearly interpolated coarse correction to the fine one,
+    /// only weighting coarse cells that are fluid. Fluid border cells weigh in
+    /// with their fixed zero.
+    fn prolongate(coarse: &Level, fine: &mut Level) {
+        // weights of the parent and of the neighbour on the other side of the
+        // fine center, 3/4 and 1/4 between evenly spaced cells
+        let weights = |fine_spans: &[Span], coarse_spans: &[Span], index: i32| {
+            let parent = Self::parent(index, fine_spans.len());
+            let neighbour = if (index - 1) % 2 == 0 {
+                parent - 1
+            } else {
+                parent + 1
+            };
+
+            let position = Self::center(fine_spans, index);
+            let near = Self::center(coarse_spans, parent);
+            let far = Self::center(coarse_spans, neighbour);
+            let w = (far - position) / (far - near);
+
+            (parent, neighbour, w, 1.0 - w)
+        };
+
+        for (i, k) in unknowns(&fine.b) {
+            let (ci, ni, wci, wni) = weights(&fine.columns, &coarse.columns, i);
+            let (ck, nk, wck, wnk) = weights(&fine.rows, &coarse.rows, k);
+
+            let mut value = 0.0;
+            let mut weight = 0.0;
+
+            for (ii, kk, w) in [
+                (ci, ck, wci * wck),
+                (ni, ck, wni * wck),
+                (ci, nk, wci * wnk),
+                (ni, nk, wni * wnk),
+            ] {
+                if coarse.b.get(ii, kk) != 0 {
+                    value += w * coarse.x.get(ii, kk);
+                    weight += w;
+                }
+            }
+
+            if weight > 0.0 {
+                fine.x.update(i, k, |x| x + value / weight);
+            }
+        }
+    }
+
+    fn v_cycle(&mut self, level: usize) {
+        if level == self.levels.len() - 1 {
+            self.levels[level].smooth(self.coarsest_iterations, false);
+            return;
+        }
+
+        self.levels[level].smooth(self.pre_smoothing, false);
+        self.levels[level].compute_residual();
+
+        {
+            let (fine, coarse) = self.levels.split_at_mut(level + 1);
+            let fine = &fine[level];
+            let coarse = &mut coarse[0];
+
+            Self::restrict(&fine.r, &fine.b, &mut coarse.rhs);
+            coarse.x.fill(0.0);
+        }
+
+        self.v_cycle(level + 1);
+
+        {
+            let (fine, coarse) = self.levels.split_at_mut(level + 1);
+            let fine = &mut fine[level];
+            let coarse = &coarse[0];
+
+            Self::prolongate(coarse, fine);
+            fine.wrap.fill_ghosts(&mut fine.x);
+        }
+
+        self.levels[level].smooth(self.post_smoothing, true);
+    }
+}
+
+impl PressureSolver for Multigrid {
+    fn begin(&mut self, system: &mut PressureSystem) {
+        if self.levels[0].width() != system.b.width()
+            || self.levels[0].height() != system.b.height()
+            || self.wrap != system.wrap
+        {
+            self.wrap = system.wrap;
+            self.build_levels(system.b.width(), system.b.height());
+        }
+
+        self.levels[0].b.copy_from(system.b);
+        self.levels[0].face_u.copy_from(system.face_u);
+        self.levels[0].face_v.copy_from(system.face_v);
+
+        for level in 1..self.levels.len() {
+            let (fine, coarse) = self.levels.split_at_mut(level);
+
+            Self::restrict_mask(&fine[level - 1].b, &mut coarse[0].b);
+            self.wrap.fill_ghosts(&mut coarse[0].b);
+            Self::restrict_faces(&fine[level - 1], &mut coarse[0]);
+        }
+    }
+
+    fn iterate(&mut self, system: &mut PressureSystem) {
+        let finest = &mut self.levels[0];
+
+        for (i, k, rhs) in finest.rhs.iter_mut() {
+            *rhs = if !is_unknown(&finest.b, i, k) {
+                0.0
+            } else {
+                system.get_divergence(i, k)
+            };
+        }
+
+        finest.x.fill(0.0);
+
+        self.v_cycle(0);
+
+        let finest = &self.levels[0];
+
+        for (i, k) in unknowns(&finest.b) {
+            system.apply_correction(i, k, finest.x.get(i, k));
+        }
+    }
+}
@@ -0,0 +1,119 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Circle { radius: f64 },
+    Rectangle { width: f64, height: f64 },
+    Polygon { points: Vec<(f64, f64)> }, // relative to the obstacle position
+}
+
+impl Shape {
+    /// Whether a point, relative to the shape's center and unrotated, is inside.
+    fn contains(&self, x: f64, y: f64) -> bool {
+        match self {
+            Shape::Circle { radius } => x * x + y * y <= radius * radius,
+            Shape::Rectangle { width, height } => x.abs() <= width / 2.0 && y.abs() <= height / 2.0,
+            Shape::Polygon { points } => {
+                // even-odd rule: count the edges a ray going right crosses
+                let mut inside = false;
+
+                for (j, &(x0, y0)) in points.iter().enumerate() {
+                    let (x1, y1) = points[(j + 1) % points.len()];
+
+                    if (y0 > y) != (y1 > y) && x < x0 + (y - y0) / (y1 - y0) * (x1 - x0) {
+                        inside = !inside;
+                    }
+                }
+
+                inside
+            }
+        }
+    }
+
+    /// Distance from the center to the farthest point of the shape, at any
+    /// rotation.
+    fn get_extent(&self) -> f64 {
+        match self {
+            Shape::Circle { radius } => *radius,
+            Shape::Rectangle { width, height } => f64::hypot(width / 2.0, height / 2.0),
+            Shape::Polygon { points } => points
+                .iter()
+                .fold(0.0, |extent, &(x, y)| f64::max(extent, f64::hypot(x, y))),
+        }
+    }
+}
+
+/// A solid shape placed on the canvas, in pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Obstacle {
+    pub id: u32, // assigned by the fluid it is added to
+    pub x: f64,
+    pub y: f64,
+    pub angle: f64,            // in radians, clockwise on the canvas
+    pub velocity: (f64, f64),  // in pixels/s
+    pub angular_velocity: f64, // in radians/s, clockwise
+    pub shape: Shape,
+}
+
+impl Obstacle {
+    pub fn new(x: f64, y: f64, angle: f64, shape: Shape) -> Obstacle {
+        Obstacle {
+            id: 0,
+            x,
+            y,
+            angle,
+            velocity: (0.0, 0.0),
+            angular_velocity: 0.0,
+            shape,
+        }
+    }
+
+    /// A polygon from absolute canvas points, positioned at their average.
+    pub fn polygon(points: &[(f64, f64)]) -> Obstacle {
+        let n = points.len().max(1) as f64;
+        let x = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let y = points.iter().map(|p| p.1).sum::<f64>() / n;
+
+        let points = points.iter().map(|&(px, py)| (px - x, py - y)).collect();
+
+        Obstacle::new(x, y, 0.0, Shape::Polygon { points })
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.velocity != (0.0, 0.0) || self.angular_velocity != 0.0
+    }
+
+    /// Moves and turns the obstacle by its velocity over `delta_t`.
+    pub fn step(&mut self, delta_t: f64) {
+        self.x += self.velocity.0 * delta_t;
+        self.y += self.velocity.1 * delta_t;
+        self.angle += self.angular_velocity * delta_t;
+    }
+
+    /// Velocity of the solid at a canvas point, including its rotation.
+    pub fn get_velocity_at(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            self.velocity.0 - self.angular_velocity * (y - self.y),
+            self.velocity.1 + self.angular_velocity * (x - self.x),
+        )
+    }
+
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        let (sin, cos) = self.angle.sin_cos();
+        let dx = x - self.x;
+        let dy = y - self.y;
+
+        self.shape
+            .contains(dx * cos + dy * sin, -dx * sin + dy * cos)
+    }
+
+    /// Canvas rectangle `(min_x, min_y, max_x, max_y)` the obstacle fits in.
+    pub fn get_bounds(&self) -> (f64, f64, f64, f64) {
+        let extent = self.shape.get_extent();
+
+        (
+            self.x - extent,
+            self.y - extent,
+            self.x + extent,
+            self.y + extent,
+        )
+    }
+}
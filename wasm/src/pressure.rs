@@ -0,0 +1,785 @@
+use std::fmt::Debug;
+
+use crate::fluid::ResidualNorm;
+use crate::grid::Grid;
+use crate::multigrid::Multigrid;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PressureSolverKind {
+    Sor,
+    Jacobi,
+    ConjugateGradient,
+    Multigrid,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SweepOrder {
+    ColumnMajor,
+    RedBlack,
+}
+
+/// Solves for the pressure that makes the velocity field divergence free.
+///
+/// Every call to `iterate` corrects `u`/`v` in place and accumulates the
+/// pressure it implied into `p`, so the caller can check the residual between
+/// iterations and stop whenever it wants.
+pub trait PressureSolver: Debug {
+    fn begin(&mut self, _system: &mut PressureSystem) {}
+    fn iterate(&mut self, system: &mut PressureSystem);
+}
+
+pub fn new_pressure_solver(
+    kind: PressureSolverKind,
+    width: usize,
+    height: usize,
+) -> Box<dyn PressureSolver> {
+    match kind {
+        PressureSolverKind::Sor => Box::new(Sor),
+        PressureSolverKind::Jacobi => Box::new(Jacobi::new(width, height)),
+        PressureSolverKind::ConjugateGradient => Box::new(ConjugateGradient::new(width, height)),
+        PressureSolverKind::Multigrid => Box::new(Multigrid::new(width, height)),
+    }
+}
+
+/// The grids of a `Fluid` that take part in the projection.
+pub struct PressureSystem<'a> {
+    pub u: &'a mut Grid<f64>,
+    pub v: &'a mut Grid<f64>,
+    pub p: &'a mut Grid<f64>,
+    pub b: &'a Grid<u8>,
+    pub solid_u: &'a Grid<f64>, // velocity the faces next to obstacles are held at
+    pub solid_v: &'a Grid<f64>,
+    pub face_u: &'a Grid<f64>, // open fraction of each u face
+    pub face_v: &'a Grid<f64>, // open fraction of each v face
+    pub overrelaxation_coefficient: f64,
+    pub jacobi_weight: f64, // damping of each Jacobi correction
+    pub sweep_order: SweepOrder,
+    pub pressure_scale: f64, // converts a velocity correction into pressure
+    pub wrap: Wrap,          // axes whose opposite edges are joined
+}
+
+/// Axes along which the domain is periodic. The border cells at either end of
+/// a wrapped axis are ghosts of the interior cells on the opposite side, and
+/// the faces between them and the interior are one and the same face.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Wrap {
+    pub x: bool,
+    pub y: bool,
+}
+
+impl Wrap {
+    /// The cell a cell of a `width` by `height` grid stands for, i.e. the
+    /// interior cell on the far side for a ghost of a wrapped axis and the
+    /// cell itself otherwise.
+    pub fn cell(&self, i: i32, k: i32, width: usize, height: usize) -> (i32, i32) {
+        (
+            if self.x { wrap_index(i, width) } else { i },
+            if self.y { wrap_index(k, height) } else { k },
+        )
+    }
+
+    /// Whether a cell is a ghost of the interior cell on the far side.
+    pub fn is_ghost(&self, i: i32, k: i32, width: usize, height: usize) -> bool {
+        self.cell(i, k, width, height) != (i, k)
+    }
+
+    /// Copies the interior cells on the far side of each wrapped axis into
+    /// the ghosts of `grid`.
+    pub fn fill_ghosts<T: Copy + Default>(&self, grid: &mut Grid<T>) {
+        let (width, height) = (grid.width() as i32, grid.height() as i32);
+
+        if self.x {
+            for k in 0..height {
+                grid.set(0, k, grid.get(width - 2, k));
+                grid.set(width - 1, k, grid.get(1, k));
+            }
+        }
+
+        if self.y {
+            for i in 0..width {
+                grid.set(i, 0, grid.get(i, height - 2));
+                grid.set(i, height - 1, grid.get(i, 1));
+            }
+        }
+    }
+}
+
+/// Brings any index along an axis of `size` cells, border included, back into
+/// its interior, as if the axis repeated every `size - 2` cells. The faces of
+/// a staggered grid repeat with the same period.
+pub fn wrap_index(index: i32, size: usize) -> i32 {
+    1 + (index - 1).rem_euclid(size as i32 - 2)
+}
+
+impl PressureSystem<'_> {
+    pub fn enforce_boundaries(&mut self) {
+        let b = self.b;
+
+        for (i, k, &fluid) in b.iter() {
+            if fluid == 0 {
+                self.u.set(i, k, self.solid_u.get(i, k));
+                self.v.set(i, k, self.solid_v.get(i, k));
+                continue;
+            }
+            if b.get(i - 1, k) == 0 {
+                self.u.set(i, k, self.solid_u.get(i, k));
+            }
+            if b.get(i, k - 1) == 0 {
+                self.v.set(i, k, self.solid_v.get(i, k));
+            }
+        }
+    }
+
+    /// Net flux out of a cell. The fluid only flows through the open part of
+    /// each face, the rest of it moves with the solid.
+    pub fn get_divergence(&self, i: i32, k: i32) -> f64 {
+        let flux_u = |i: i32| {
+            let open = self.face_u.get(i, k);
+            open * self.u.get(i, k) + (1.0 - open) * self.solid_u.get(i, k)
+        };
+        let flux_v = |k: i32| {
+            let open = self.face_v.get(i, k);
+            open * self.v.get(i, k) + (1.0 - open) * self.solid_v.get(i, k)
+        };
+
+        flux_u(i + 1) - flux_u(i) + flux_v(k + 1) - flux_v(k)
+    }
+
+    pub fn get_residual(&self, norm: ResidualNorm) -> f64 {
+        let mut max: f64 = 0.0;
+        let mut sum = 0.0;
+        let mut count = 0;
+
+        for (i, k) in unknowns(self.b) {
+            let divergence = self.get_divergence(i, k);
+
+            max = max.max(divergence.abs());
+            sum += divergence * divergence;
+            count += 1;
+        }
+
+        match norm {
+            ResidualNorm::Max => max,
+            ResidualNorm::Rms if count > 0 => f64::sqrt(sum / count as f64),
+            ResidualNorm::Rms => 0.0,
+        }
+    }
+
+    /// Whether the face grids are one cell wider (`u`) or taller (`v`) than
+    /// the cells, which the unchecked loops of `Sor` rely on.
+    pub fn has_matching_sizes(&self) -> bool {
+        let (width, height) = (self.b.width(), self.b.height());
+        let is_sized =
+            |grid: &Grid<f64>, w: usize, h: usize| grid.width() == w && grid.height() == h;
+
+        is_sized(self.p, width, height)
+            && [&*self.u, self.solid_u, self.face_u]
+                .iter()
+                .all(|grid| is_sized(grid, width + 1, height))
+            && [&*self.v, self.solid_v, self.face_v]
+                .iter()
+                .all(|grid| is_sized(grid, width, height + 1))
+    }
+
+    pub fn is_unknown(&self, i: i32, k: i32) -> bool {
+        is_unknown(self.b, i, k)
+    }
+
+    /// Total open area of the faces of a cell, i.e. the diagonal of the
+    /// Poisson matrix.
+    pub fn get_open_faces(&self, i: i32, k: i32) -> f64 {
+        get_open_faces(self.face_u, self.face_v, i, k)
+    }
+
+    /// Pushes `correction` out of the cell through every face that is at
+    /// least partly open, lowering its divergence by `correction * open faces`.
+    pub fn apply_correction(&mut self, i: i32, k: i32, correction: f64) {
+        let is_open = |open: f64| if open > 0.0 { 1.0 } else { 0.0 };
+
+        let b0 = is_open(self.face_u.get(i, k));
+        let b1 = is_open(self.face_u.get(i + 1, k));
+        let b2 = is_open(self.face_v.get(i, k));
+        let b3 = is_open(self.face_v.get(i, k + 1));
+
+        self.u.update(i, k, |v| v + correction * b0);
+        self.u.update(i + 1, k, |v| v - correction * b1);
+        self.v.update(i, k, |v| v + correction * b2);
+        self.v.update(i, k + 1, |v| v - correction * b3);
+
+        let pressure_scale = self.pressure_scale;
+        self.p.update(i, k, |p| p - correction * pressure_scale);
+
+        self.join_seams(i, k);
+    }
+
+    /// Copies the velocity a cell next to a wrapped edge just set on the face
+    /// it shares with the far side onto that face's other copy.
+    fn join_seams(&mut self, i: i32, k: i32) {
+        let (width, height) = (self.b.width() as i32, self.b.height() as i32);
+
+        if self.wrap.x {
+            if i == 1 {
+                self.u.set(width - 1, k, self.u.get(1, k));
+            } else if i == width - 2 {
+                self.u.set(1, k, self.u.get(width - 1, k));
+            }
+        }
+
+        if self.wrap.y {
+            if k == 1 {
+                self.v.set(i, height - 1, self.v.get(i, 1));
+            } else if k == height - 2 {
+                self.v.set(i, 1, self.v.get(i, height - 1));
+            }
+        }
+    }
+}
+
+/// Whether the pressure of a cell is solved for. Cells on the border of the
+/// grid never are: they are either solid or, next to an open edge, fluid held
+/// at zero pressure.
+pub fn is_unknown(b: &Grid<u8>, i: i32, k: i32) -> bool {
+    b.get(i, k) != 0 && i > 0 && k > 0 && i < b.width() as i32 - 1 && k < b.height() as i32 - 1
+}
+
+/// Every cell whose pressure is solved for, see `is_unknown`.
+pub fn unknowns(b: &Grid<u8>) -> impl Iterator<Item = (i32, i32)> {
+    b.interior()
+        .filter(|&(_, _, &fluid)| fluid != 0)
+        .map(|(i, k, _)| (i, k))
+}
+
+pub fn get_open_faces(face_u: &Grid<f64>, face_v: &Grid<f64>, i: i32, k: i32) -> f64 {
+    face_u.get(i, k) + face_u.get(i + 1, k) + face_v.get(i, k) + face_v.get(i, k + 1)
+}
+
+/// Open area of the faces between a cell and its four neighbours, the
+/// neighbours across a wrapped edge being on the far side.
+fn get_neighbour_faces(
+    face_u: &Grid<f64>,
+    face_v: &Grid<f64>,
+    wrap: Wrap,
+    i: i32,
+    k: i32,
+) -> [(i32, i32, f64); 4] {
+    let (width, height) = (face_v.width(), face_u.height());
+    let neighbour = |i: i32, k: i32| wrap.cell(i, k, width, height);
+
+    [
+        (neighbour(i - 1, k), face_u.get(i, k)),
+        (neighbour(i + 1, k), face_u.get(i + 1, k)),
+        (neighbour(i, k - 1), face_v.get(i, k)),
+        (neighbour(i, k + 1), face_v.get(i, k + 1)),
+    ]
+    .map(|((ii, kk), open)| (ii, kk, open))
+}
+
+/// Product of the Poisson matrix with `x` at a fluid cell, `x` being zero
+/// wherever it is not an unknown.
+pub fn apply_laplacian(
+    face_u: &Grid<f64>,
+    face_v: &Grid<f64>,
+    wrap: Wrap,
+    x: &Grid<f64>,
+    i: i32,
+    k: i32,
+) -> f64 {
+    get_neighbour_faces(face_u, face_v, wrap, i, k)
+        .iter()
+        .map(|&(ii, kk, open)| open * (x.get(i, k) - x.get(ii, kk)))
+        .sum()
+}
+
+/// Whether a fluid cell has its pressure held at zero, i.e. an edge is open.
+/// Otherwise the pressure is only defined up to a constant. The ghosts of a
+/// wrapped edge are not held, they stand for the cells on the far side.
+fn has_fixed_pressure(b: &Grid<u8>, wrap: Wrap) -> bool {
+    b.iter().any(|(i, k, &fluid)| {
+        fluid != 0 && !is_unknown(b, i, k) && !wrap.is_ghost(i, k, b.width(), b.height())
+    })
+}
+
+fn fit_to(grid: &mut Grid<f64>, b: &Grid<u8>) {
+    if grid.width() != b.width() || grid.height() != b.height() {
+        grid.resize(b.width(), b.height());
+    }
+}
+
+fn dot(a: &Grid<f64>, b: &Grid<f64>, mask: &Grid<u8>) -> f64 {
+    unknowns(mask).map(|(i, k)| a.get(i, k) * b.get(i, k)).sum()
+}
+
+/// Overrelaxed Gauss-Seidel, updating the cells in place one after the other.
+#[derive(Debug, Clone)]
+pub struct Sor;
+
+impl Sor {
+    pub fn sweep(system: &mut PressureSystem) {
+        assert!(
+            system.has_matching_sizes(),
+            "pressure grids do not match the cells"
+        );
+
+        let width = system.b.width();
+        let height = system.b.height();
+
+        if width < 3 || height < 3 {
+            return;
+        }
+
+        // SAFETY: the sizes were checked above and the loops skip the border,
+        // so every cell and face `relax` touches is in range
+        match system.sweep_order {
+            SweepOrder::ColumnMajor => {
+                for i in 1..width - 1 {
+                    for k in 1..height - 1 {
+                        unsafe { Self::relax(system, i, k) };
+                    }
+                }
+            }
+            SweepOrder::RedBlack => {
+                // cells of one colour only share faces with cells of the other,
+                // so every relaxation within a half sweep is independent
+                for colour in 0..2 {
+                    for i in 1..width - 1 {
+                        for k in (2 - (i + colour) % 2..height - 1).step_by(2) {
+                            unsafe { Self::relax(system, i, k) };
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Same as `get_divergence` and `apply_correction` on one cell, without
+    /// the bounds checks.
+    ///
+    /// # Safety
+    ///
+    /// The grids must pass `has_matching_sizes` and the cell must not be on
+    /// the border.
+    unsafe fn relax(system: &mut PressureSystem, i: usize, k: usize) {
+        // SAFETY: the caller guarantees the cell and its faces are in range
+        unsafe {
+            if system.b.get_unchecked(i, k) == 0 {
+                return;
+            }
+
+            let w0 = system.face_u.get_unchecked(i, k);
+            let w1 = system.face_u.get_unchecked(i + 1, k);
+            let w2 = system.face_v.get_unchecked(i, k);
+            let w3 = system.face_v.get_unchecked(i, k + 1);
+            let b = w0 + w1 + w2 + w3;
+
+            if b == 0.0 {
+                return;
+            }
+
+            let flux_u = |i: usize, open: f64| {
+                open * system.u.get_unchecked(i, k)
+                    + (1.0 - open) * system.solid_u.get_unchecked(i, k)
+            };
+            let flux_v = |k: usize, open: f64| {
+                open * system.v.get_unchecked(i, k)
+                    + (1.0 - open) * system.solid_v.get_unchecked(i, k)
+            };
+            let divergence = flux_u(i + 1, w1) - flux_u(i, w0) + flux_v(k + 1, w3) - flux_v(k, w2);
+
+            let correction = (divergence * system.overrelaxation_coefficient) / b;
+            let is_open = |open: f64| if open > 0.0 { 1.0 } else { 0.0 };
+
+            let u = &mut *system.u;
+            u.set_unchecked(i, k, u.get_unchecked(i, k) + correction * is_open(w0));
+            u.set_unchecked(
+                i + 1,
+                k,
+                u.get_unchecked(i + 1, k) - correction * is_open(w1),
+            );
+
+            let v = &mut *system.v;
+            v.set_unchecked(i, k, v.get_unchecked(i, k) + correction * is_open(w2));
+            v.set_unchecked(
+                i,
+                k + 1,
+                v.get_unchecked(i, k + 1) - correction * is_open(w3),
+            );
+
+            let p = &mut *system.p;
+            p.set_unchecked(
+                i,
+                k,
+                p.get_unchecked(i, k) - correction * system.pressure_scale,
+            );
+        }
+
+        system.join_seams(i as i32, k as i32);
+    }
+}
+
+impl PressureSolver for Sor {
+    fn iterate(&mut self, system: &mut PressureSystem) {
+        Self::sweep(system);
+    }
+}
+
+/// Weighted Jacobi iteration: every correction is computed from the divergence
+/// of the previous iteration, then applied all at once scaled by
+/// `jacobi_weight`. Undamped, the checkerboard error of a closed box never
+/// decays.
+#[derive(Debug, Clone)]
+pub struct Jacobi {
+    corrections: Grid<f64>,
+}
+
+impl Jacobi {
+    pub fn new(width: usize, height: usize) -> Jacobi {
+        Jacobi {
+            corrections: Grid::new(width, height),
+        }
+    }
+}
+
+impl PressureSolver for Jacobi {
+    fn begin(&mut self, system: &mut PressureSystem) {
+        fit_to(&mut self.corrections, system.b);
+    }
+
+    fn iterate(&mut self, system: &mut PressureSystem) {
+        for (i, k, correction) in self.corrections.iter_mut() {
+            let b = system.get_open_faces(i, k);
+
+            *correction = if !system.is_unknown(i, k) || b == 0.0 {
+                0.0
+            } else {
+                system.jacobi_weight * system.get_divergence(i, k) / b
+            };
+        }
+
+        for (i, k, &correction) in self.corrections.iter() {
+            if correction != 0.0 {
+                system.apply_correction(i, k, correction);
+            }
+        }
+    }
+}
+
+/// Conjugate gradient preconditioned with a modified incomplete Cholesky
+/// factorisation (MIC(0)) of the Poisson matrix.
+///
+/// Each iteration moves the velocity by the step taken along the search
+/// direction, so the velocity divergence is always the current CG residual.
+#[derive(Debug, Clone)]
+pub struct ConjugateGradient {
+    residual: Grid<f64>,
+    auxiliary: Grid<f64>,
+    search: Grid<f64>,
+    precon: Grid<f64>,
+    sigma: f64,
+    initial_sigma: f64,
+}
+
+impl ConjugateGradient {
+    const TUNING: f64 = 0.97;
+    const SAFETY: f64 = 0.25;
+    // relative to the first `sigma`; iterating past this only amplifies roundoff
+    const TOLERANCE: f64 = 1e-24;
+
+    pub fn new(width: usize, height: usize) -> ConjugateGradient {
+        ConjugateGradient {
+            residual: Grid::new(width, height),
+            auxiliary: Grid::new(width, height),
+            search: Grid::new(width, height),
+            precon: Grid::new(width, height),
+            sigma: 0.0,
+            initial_sigma: 0.0,
+        }
+    }
+
+    fn build_preconditioner(&mut self, system: &PressureSystem) {
+        let b = system.b;
+        let precon = &mut self.precon;
+
+        // column by column, as each cell uses the ones left of and above it
+        for (i, k) in b.indices() {
+            if !is_unknown(b, i, k) {
+                precon.set(i, k, 0.0);
+                continue;
+            }
+
+            let diagonal = system.get_open_faces(i, k);
+
+            // off-diagonals are minus the open area between two unknowns
+            let coupling = |open: f64, ii: i32, kk: i32| {
+                if is_unknown(b, ii, kk) { -open } else { 0.0 }
+            };
+
+            let left = coupling(system.face_u.get(i, k), i - 1, k);
+            let top = coupling(system.face_v.get(i, k), i, k - 1);
+
+            let left_precon = precon.get(i - 1, k);
+            let top_precon = precon.get(i, k - 1);
+
+            // A(i-1,k) coupled to (i-1,k+1) and A(i,k-1) coupled to (i+1,k-1)
+            let left_down = if left != 0.0 {
+                coupling(system.face_v.get(i - 1, k + 1), i - 1, k + 1)
+            } else {
+                0.0
+            };
+            let top_right = if top != 0.0 {
+                coupling(system.face_u.get(i + 1, k - 1), i + 1, k - 1)
+            } else {
+                0.0
+            };
+
+            let mut e = diagonal
+                - (left * left_precon).powi(2)
+                - (top * top_precon).powi(2)
+                - Self::TUNING
+                    * (left * left_down * left_precon.powi(2)
+                        + top * top_right * top_precon.powi(2));
+
+            if e < Self::SAFETY * diagonal {
+                e = diagonal;
+            }
+
+            precon.set(i, k, if e > 0.0 { 1.0 / e.sqrt() } else { 0.0 });
+        }
+    }
+
+    /// Solves `M z = r` into `auxiliary`.
+    fn apply_preconditioner(&mut self, system: &PressureSystem) {
+        let b = system.b;
+        let face_u = system.face_u;
+        let face_v = system.face_v;
+
+        // forward substitution, reusing `auxiliary` for the intermediate result,
+        // in the same order as the factorisation
+        for (i, k) in b.indices() {
+            if !is_unknown(b, i, k) {
+                self.auxiliary.set(i, k, 0.0);
+                continue;
+            }
+
+            let mut t = self.residual.get(i, k);
+
+            if is_unknown(b, i - 1, k) {
+                t += face_u.get(i, k) * self.precon.get(i - 1, k) * self.auxiliary.get(i - 1, k);
+            }
+            if is_unknown(b, i, k - 1) {
+                t += face_v.get(i, k) * self.precon.get(i, k - 1) * self.auxiliary.get(i, k - 1);
+            }
+
+            self.auxiliary.set(i, k, t * self.precon.get(i, k));
+        }
+
+        // backward substitution, from the last cell back to the first
+        for (i, k) in b.indices().rev() {
+            if !is_unknown(b, i, k) {
+                continue;
+            }
+
+            let mut t = self.auxiliary.get(i, k);
+
+            if is_unknown(b, i + 1, k) {
+                t += face_u.get(i + 1, k) * self.precon.get(i, k) * self.auxiliary.get(i + 1, k);
+            }
+            if is_unknown(b, i, k + 1) {
+                t += face_v.get(i, k + 1) * self.precon.get(i, k) * self.auxiliary.get(i, k + 1);
+            }
+
+            self.auxiliary.set(i, k, t * self.precon.get(i, k));
+        }
+    }
+}
+
+impl PressureSolver for ConjugateGradient {
+    fn begin(&mut self, system: &mut PressureSystem) {
+        fit_to(&mut self.residual, system.b);
+        fit_to(&mut self.auxiliary, system.b);
+        fit_to(&mut self.search, system.b);
+        fit_to(&mut self.precon, system.b);
+
+        for (i, k, r) in self.residual.iter_mut() {
+            *r = if !system.is_unknown(i, k) {
+                0.0
+            } else {
+                system.get_divergence(i, k)
+            };
+        }
+
+        // without a fixed pressure anywhere the matrix is singular, and only
+        // the part of the divergence with zero mean can be removed
+        if !has_fixed_pressure(system.b, system.wrap) {
+            let (sum, count) = unknowns(system.b).fold((0.0, 0.0), |(sum, count), (i, k)| {
+                (sum + self.residual.get(i, k), count + 1.0)
+            });
+
+            if count > 0.0 {
+                for (i, k) in unknowns(system.b) {
+                    self.residual.update(i, k, |r| r - sum / count);
+                }
+            }
+        }
+
+        self.build_preconditioner(system);
+        self.apply_preconditioner(system);
+
+        self.search.swap(&mut self.auxiliary);
+        self.sigma = dot(&self.residual, &self.search, system.b);
+        self.initial_sigma = self.sigma;
+    }
+
+    fn iterate(&mut self, system: &mut PressureSystem) {
+        if self.sigma <= Self::TOLERANCE * self.initial_sigma {
+            return;
+        }
+
+        let b = system.b;
+
+        for (i, k, q) in self.auxiliary.iter_mut() {
+            *q = if !is_unknown(b, i, k) {
+                0.0
+            } else {
+                apply_laplacian(
+                    system.face_u,
+                    system.face_v,
+                    system.wrap,
+                    &self.search,
+                    i,
+                    k,
+                )
+            };
+        }
+
+        let curvature = dot(&self.search, &self.auxiliary, b);
+
+        if curvature <= 0.0 {
+            self.sigma = 0.0;
+            return;
+        }
+
+        let alpha = self.sigma / curvature;
+
+        for (i, k) in unknowns(b) {
+            system.apply_correction(i, k, alpha * self.search.get(i, k));
+        }
+
+        // `auxiliary` is zero wherever the pressure is not solved for
+        self.residual
+            .zip_with(&self.auxiliary, |r, q| r - alpha * q);
+
+        self.apply_preconditioner(system);
+
+        let sigma = dot(&self.residual, &self.auxiliary, b);
+        let beta = sigma / self.sigma;
+
+        self.search.zip_with(&self.auxiliary, |s, z| z + beta * s);
+
+        self.sigma = sigma;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Domain;
+
+    const KINDS: [PressureSolverKind; 4] = [
+        PressureSolverKind::Sor,
+        PressureSolverKind::Jacobi,
+        PressureSolverKind::ConjugateGradient,
+        PressureSolverKind::Multigrid,
+    ];
+
+    #[test]
+    fn solvers_reduce_residual_in_closed_box() {
+        for kind in KINDS {
+            let reduction = Domain::new(16, 12).solve(kind, 2000);
+            assert!(reduction < 1e-3, "{kind:?} left {reduction:e}");
+        }
+    }
+
+    #[test]
+    fn solvers_reduce_residual_around_circle() {
+        for kind in KINDS {
+            let reduction = Domain::new(16, 12)
+                .with_circle(8.0, 6.0, 3.0)
+                .solve(kind, 2000);
+            assert!(reduction < 1e-3, "{kind:?} left {reduction:e}");
+        }
+    }
+
+    #[test]
+    fn conjugate_gradient_stays_converged() {
+        let reduction = Domain::new(66, 40)
+            .with_circle(33.0, 20.0, 6.0)
+            .solve(PressureSolverKind::ConjugateGradient, 2000);
+        assert!(reduction < 1e-9, "left {reduction:e}");
+    }
+
+    #[test]
+    fn solvers_reduce_residual_with_open_edge() {
+        for kind in KINDS {
+            let reduction = Domain::new(16, 12).with_open_right().solve(kind, 2000);
+            assert!(reduction < 1e-3, "{kind:?} left {reduction:e}");
+        }
+    }
+
+    #[test]
+    fn multigrid_converges_with_open_edge() {
+        for (width, height) in [(66, 40), (358, 202)] {
+            let reduction = Domain::new(width, height)
+                .with_open_right()
+                .solve(PressureSolverKind::Multigrid, 10);
+            assert!(reduction < 1e-8, "{width}x{height} left {reduction:e}");
+        }
+    }
+
+    #[test]
+    fn solvers_converge_in_wind_tunnel() {
+        for kind in KINDS {
+            let reduction = Domain::new(16, 12)
+                .with_circle(6.0, 6.0, 2.0)
+                .with_open_right()
+                .with_inflow(5.0)
+                .solve(kind, 5000);
+            assert!(reduction < 1e-3, "{kind:?} left {reduction:e}");
+        }
+
+        let reduction = Domain::new(358, 202)
+            .with_circle(90.0, 101.0, 20.0)
+            .with_open_right()
+            .with_inflow(5.0)
+            .solve(PressureSolverKind::Multigrid, 10);
+        assert!(reduction < 1e-8, "multigrid left {reduction:e}");
+    }
+
+    #[test]
+    fn solvers_reduce_residual_across_wrapped_edges() {
+        for (x, y) in [(true, false), (false, true), (true, true)] {
+            for kind in KINDS {
+                let mut domain = Domain::new(16, 12)
+                    .with_circle(3.0, 4.0, 2.0)
+                    .with_wrap(x, y);
+                let reduction = domain.solve(kind, 2000);
+
+                assert!(reduction < 1e-3, "{kind:?} {x} {y} left {reduction:e}");
+                assert!(domain.has_joined_seams(), "{kind:?} {x} {y} split a seam");
+            }
+        }
+    }
+
+    #[test]
+    fn multigrid_converges_across_wrapped_edges() {
+        for (x, y) in [(true, false), (true, true)] {
+            let reduction = Domain::new(66, 40)
+                .with_circle(10.0, 20.0, 6.0)
+                .with_wrap(x, y)
+                .solve(PressureSolverKind::Multigrid, 10);
+            assert!(reduction < 1e-8, "{x} {y} left {reduction:e}");
+        }
+    }
+}
@@ -1,13 +1,24 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use crate::fluid::{Field, Fluid, FluidSimulation};
-use crate::util::{gaussian, map, RingBuffer};
+use crate::fluid::{DYE_CHANNELS, Field, Fluid, FluidSimulation, Interpolation, WindTunnel};
+use crate::obstacle::{Obstacle, Shape};
+use crate::util::{RingBuffer, gaussian, hsl_to_rgb, map};
 use wasm_bindgen::prelude::*;
 
 type AnimationFrameCb = Rc<RefCell<Option<Closure<dyn FnMut(f64)>>>>;
 type MouseEventCb = Rc<Closure<dyn FnMut(web_sys::PointerEvent)>>;
 type ResizeEventCb = Rc<Closure<dyn FnMut(web_sys::Event)>>;
+type ScrollEventCb = Rc<Closure<dyn FnMut(web_sys::Event)>>;
+
+/// What dragging the pointer over the canvas does.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerMode {
+    Stir,  // pushes and dyes the fluid
+    Paint, // paints solid cells, erasing while shift is held
+    Erase, // erases painted cells
+}
 
 pub struct Scene {
     pub fluid: Fluid,
@@ -15,11 +26,23 @@ pub struct Scene {
 
     pub mouse_radius: i32,
     pub subdivisions: u8,
+    pub interpolation: Interpolation, // how smoke is sampled between grid cells
     is_mouse_down: bool,
     last_time: f64,
     last_mouse_xy: (i32, i32),
     then: f64,
+    accumulator: f64, // simulated time owed to the wall clock, in ms
+    blend: f64,       // how far into the last step the rendered state is, 0 to 1
     max_velocity: f64,
+    dye_color: [f64; DYE_CHANNELS],
+    dye_hue: f64,
+    dye_hue_speed: f64,  // degrees per second, 0 keeps `dye_color`
+    paddle: Option<u32>, // obstacle following the pointer
+    paddle_target: (f64, f64),
+    dom_selectors: Vec<String>, // elements the smoke flows around
+    dom_obstacles: Vec<u32>,
+    dom_dirty: bool, // refresh the DOM obstacles on the next frame
+    pointer_mode: PointerMode,
 
     enable_playing: bool,
     enable_mouse_move: bool,
@@ -28,6 +51,8 @@ pub struct Scene {
 
     show_smoke: bool,
     show_velocity_colors: bool,
+    show_pressure: bool,
+    show_dye: bool,
     show_gridlines: bool,
     show_center_velocities: bool,
     ready: bool,
@@ -37,11 +62,15 @@ pub struct Scene {
     mouse_move_cb: Option<MouseEventCb>,
     mouse_down_cb: Option<MouseEventCb>,
     resize_cb: Option<ResizeEventCb>,
+    scroll_cb: Option<ScrollEventCb>,
 
     time_to_next_frame_ring: RingBuffer,
 }
 
 impl Scene {
+    const MAX_FRAME_TIME: f64 = 250.0; // ms
+    const MAX_STEPS_PER_FRAME: usize = 4;
+
     pub fn new(canvas: web_sys::HtmlCanvasElement, fluid: Fluid) -> Scene {
         let max_velocity =
             f64::min(canvas.width() as f64, canvas.height() as f64) * fluid.square_size;
@@ -54,17 +83,30 @@ impl Scene {
             canvas,
             mouse_radius,
             subdivisions: 1,
+            interpolation: Interpolation::Bilinear,
             max_velocity,
+            dye_color: hsl_to_rgb(249.0, 100.0, 80.0),
+            dye_hue: 0.0,
+            dye_hue_speed: 0.0,
+            paddle: None,
+            paddle_target: (0.0, 0.0),
+            dom_selectors: vec![],
+            dom_obstacles: vec![],
+            dom_dirty: false,
+            pointer_mode: PointerMode::Stir,
             is_mouse_down: false,
             last_time: -1.0,
             last_mouse_xy: (0, 0),
             then: 0.0,
+            accumulator: 0.0,
+            blend: 1.0,
             ready: false,
             animation_id: None,
             animation_cb: None,
             mouse_move_cb: None,
             mouse_down_cb: None,
             resize_cb: None,
+            scroll_cb: None,
 
             enable_playing: true,
             enable_mouse_move: true,
@@ -75,11 +117,23 @@ impl Scene {
             show_center_velocities: false,
             show_smoke: true,
             show_velocity_colors: false,
+            show_pressure: false,
+            show_dye: false,
 
             time_to_next_frame_ring,
         }
     }
 
+    /// Samples a field as it is drawn, blended between the last two steps.
+    fn sample(&self, x: f64, y: f64, field: Field) -> f64 {
+        self.sample_with(x, y, field, Interpolation::Bilinear)
+    }
+
+    fn sample_with(&self, x: f64, y: f64, field: Field, interpolation: Interpolation) -> f64 {
+        self.fluid
+            .interpolate_between(x, y, field, interpolation, self.blend)
+    }
+
     fn get_ctx(&self) -> web_sys::CanvasRenderingContext2d {
         self.canvas
             .get_context("2d")
@@ -89,41 +143,14 @@ impl Scene {
             .unwrap()
     }
 
+    /// Steps the simulation and draws it, returning how long that took in
+    /// seconds.
     pub fn draw_next_frame(&mut self) -> f64 {
         let now = web_sys::window().unwrap().performance().unwrap().now();
 
-        self.clear_canvas();
-        if self.enable_projection {
-            self.fluid.projection();
-        }
-        if self.enable_advection {
-            self.fluid.advection();
-        }
-
-        let ctx = self.get_ctx();
-        let scale = self.fluid.square_size / self.subdivisions as f64;
-
-        for x in 0..self.fluid.b.width() {
-            for y in 0..self.fluid.b.height() {
-                for i in 0..self.subdivisions {
-                    for k in 0..self.subdivisions {
-                        if self.show_velocity_colors {
-                            self.draw_velocity_colors(&ctx, scale, x, y, i, k);
-                        } else if self.show_smoke {
-                            self.draw_smoke(&ctx, scale, x, y, i, k);
-                        }
-
-                        if self.show_center_velocities {
-                            self.draw_center_velocities(&ctx, scale, x, y, i, k);
-                        }
-                    }
-                }
-            }
-        }
-
-        if self.show_gridlines {
-            self.draw_gridlines(&ctx);
-        }
+        self.blend = 1.0;
+        self.simulate();
+        self.render();
 
         let then = web_sys::window().unwrap().performance().unwrap().now();
         let elapsed = then - now;
@@ -143,6 +170,86 @@ impl Scene {
         elapsed / 1000.0
     }
 
+    /// Advances the simulation by one fixed `delta_t`.
+    fn simulate(&mut self) {
+        self.fluid.save_previous_state();
+
+        if self.dye_hue_speed != 0.0 {
+            self.dye_hue =
+                (self.dye_hue + self.dye_hue_speed * self.fluid.delta_t).rem_euclid(360.0);
+            self.dye_color = hsl_to_rgb(self.dye_hue, 100.0, 65.0);
+        }
+        if let Some(wind_tunnel) = self.fluid.wind_tunnel.as_mut() {
+            wind_tunnel.streak_color = self.dye_color;
+        }
+
+        // the paddle has to reach the pointer over the whole step, whatever
+        // it is split into
+        self.move_paddle();
+
+        // fast strokes would move the fluid several cells in one step, so the
+        // step is split to keep advection accurate
+        let substeps = self.fluid.get_substeps();
+        let delta_t = self.fluid.delta_t;
+
+        self.fluid.delta_t = delta_t / substeps as f64;
+
+        for _ in 0..substeps {
+            self.step();
+        }
+
+        self.fluid.delta_t = delta_t;
+        self.fluid.last_substeps = substeps;
+    }
+
+    fn render(&mut self) {
+        self.clear_canvas();
+
+        let ctx = self.get_ctx();
+        let scale = self.fluid.square_size / self.subdivisions as f64;
+        let max_pressure = self.get_max_pressure();
+
+        for (x, y, _) in self.fluid.b.iter() {
+            for i in 0..self.subdivisions {
+                for k in 0..self.subdivisions {
+                    if self.show_velocity_colors {
+                        self.draw_velocity_colors(&ctx, scale, x, y, i, k);
+                    } else if self.show_pressure {
+                        self.draw_pressure(&ctx, scale, x, y, i, k, max_pressure);
+                    } else if self.show_smoke && self.show_dye {
+                        self.draw_dye(&ctx, scale, x, y, i, k);
+                    } else if self.show_smoke {
+                        self.draw_smoke(&ctx, scale, x, y, i, k);
+                    }
+
+                    if self.show_center_velocities {
+                        self.draw_center_velocities(&ctx, scale, x, y, i, k);
+                    }
+                }
+            }
+        }
+
+        self.draw_obstacles(&ctx);
+
+        if self.show_gridlines {
+            self.draw_gridlines(&ctx);
+        }
+    }
+
+    /// One substep of `simulate`.
+    fn step(&mut self) {
+        self.fluid.move_obstacles();
+        if self.enable_projection {
+            self.fluid.projection();
+        }
+        if self.enable_advection {
+            self.fluid.advection();
+            self.fluid.diffusion();
+            self.fluid.apply_buoyancy();
+            self.fluid.confine_vorticity();
+        }
+    }
+
     pub fn clear(&mut self) {
         self.fluid.clear();
         self.draw_next_frame();
@@ -169,24 +276,18 @@ impl Scene {
         &self,
         ctx: &web_sys::CanvasRenderingContext2d,
         scale: f64,
-        x: usize,
-        y: usize,
+        x: i32,
+        y: i32,
         i: u8,
         k: u8,
     ) {
-        let x = x as i32;
-        let y = y as i32;
         let i = i as f64;
         let k = k as f64;
 
         let (xx, yy) = self.fluid.get_xy_from_grid_indices(x, y, None);
 
-        let v = self
-            .fluid
-            .interpolate(xx + (i + 0.5) * scale, yy + (k + 0.5) * scale, Field::V);
-        let u = self
-            .fluid
-            .interpolate(xx + (i + 0.5) * scale, yy + (k + 0.5) * scale, Field::U);
+        let v = self.sample(xx + (i + 0.5) * scale, yy + (k + 0.5) * scale, Field::V);
+        let u = self.sample(xx + (i + 0.5) * scale, yy + (k + 0.5) * scale, Field::U);
 
         let length = f64::sqrt(v * v + u * u);
 
@@ -196,25 +297,67 @@ impl Scene {
         ctx.fill_rect(xx + (i * scale), yy + (k * scale), scale + 1.0, scale + 1.0);
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_pressure(
+        &self,
+        ctx: &web_sys::CanvasRenderingContext2d,
+        scale: f64,
+        x: i32,
+        y: i32,
+        i: u8,
+        k: u8,
+        max_pressure: f64,
+    ) {
+        let i = i as f64;
+        let k = k as f64;
+
+        let (xx, yy) = self.fluid.get_xy_from_grid_indices(x, y, None);
+
+        let p = self
+            .fluid
+            .interpolate(xx + (i + 0.5) * scale, yy + (k + 0.5) * scale, Field::P);
+
+        let t = if max_pressure > 0.0 {
+            (p / max_pressure).clamp(-1.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let hue = if t < 0.0 { 240.0 } else { 0.0 };
+        let lightness = map(t.abs(), 0.0, 1.0, 10.0, 50.0);
+
+        ctx.set_fill_style_str(&format!("hsl({}, 100%, {:.2}%)", hue, lightness));
+        ctx.fill_rect(xx + (i * scale), yy + (k * scale), scale + 1.0, scale + 1.0);
+    }
+
+    fn get_max_pressure(&self) -> f64 {
+        self.fluid
+            .get_pressure()
+            .iter()
+            .fold(0.0, |max, (_, _, p)| f64::max(max, p.abs()))
+    }
+
     pub fn draw_smoke(
         &self,
         ctx: &web_sys::CanvasRenderingContext2d,
         scale: f64,
-        x: usize,
-        y: usize,
+        x: i32,
+        y: i32,
         i: u8,
         k: u8,
     ) {
-        let x = x as i32;
-        let y = y as i32;
         let i = i as f64;
         let k = k as f64;
 
         let (xx, yy) = self.fluid.get_xy_from_grid_indices(x, y, None);
 
         let s = if self.ready {
-            self.fluid
-                .interpolate(xx + (i + 0.5) * scale, yy + (k + 0.5) * scale, Field::S)
+            self.sample_with(
+                xx + (i + 0.5) * scale,
+                yy + (k + 0.5) * scale,
+                Field::S,
+                self.interpolation,
+            )
         } else {
             0.0
         };
@@ -248,6 +391,242 @@ impl Scene {
         ctx.fill_rect(xx + (i * scale), yy + (k * scale), scale + 1.0, scale + 1.0);
     }
 
+    pub fn draw_dye(
+        &self,
+        ctx: &web_sys::CanvasRenderingContext2d,
+        scale: f64,
+        x: i32,
+        y: i32,
+        i: u8,
+        k: u8,
+    ) {
+        let i = i as f64;
+        let k = k as f64;
+
+        let (xx, yy) = self.fluid.get_xy_from_grid_indices(x, y, None);
+        let (px, py) = (xx + (i + 0.5) * scale, yy + (k + 0.5) * scale);
+
+        let s = if self.ready {
+            self.sample_with(px, py, Field::S, self.interpolation)
+        } else {
+            0.0
+        };
+
+        let t = (s / self.max_velocity).clamp(0.0, 1.0);
+        let background = hsl_to_rgb(253.64, 28.2, 15.20);
+
+        let mut rgb = [0.0; DYE_CHANNELS];
+
+        for (channel, value) in rgb.iter_mut().enumerate() {
+            let color = if s > 0.0 {
+                (self.sample_with(px, py, Field::Dye(channel), self.interpolation) / s)
+                    .clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+
+            *value = map(t, 0.0, 1.0, background[channel], color) * 255.0;
+        }
+
+        ctx.set_fill_style_str(&format!("rgb({:.0}, {:.0}, {:.0})", rgb[0], rgb[1], rgb[2]));
+        ctx.fill_rect(xx + (i * scale), yy + (k * scale), scale + 1.0, scale + 1.0);
+    }
+
+    pub fn set_dye_color(&mut self, color: [f64; DYE_CHANNELS]) {
+        self.dye_color = color;
+        self.dye_hue_speed = 0.0;
+        self.show_dye = true;
+    }
+
+    /// Blows `speed` pixels/s through the canvas from the left, carrying
+    /// `streaks` lines of dye, or stops when `speed` is `None`.
+    pub fn set_wind_tunnel(&mut self, speed: Option<f64>, streaks: usize) {
+        let wind_tunnel = speed.map(|speed| WindTunnel {
+            speed,
+            streaks,
+            streak_smoke: self.max_velocity * 3.0,
+            streak_color: self.dye_color,
+        });
+
+        self.fluid.set_wind_tunnel(wind_tunnel);
+        self.show_dye |= streaks > 0;
+    }
+
+    /// Replaces stirring with a circular obstacle of `radius` pixels that
+    /// follows the pointer and pushes the fluid, or removes it when `None`.
+    pub fn set_paddle(&mut self, radius: Option<f64>) {
+        if let Some(id) = self.paddle.take() {
+            self.fluid.remove_obstacle(id);
+        }
+
+        if let Some(radius) = radius {
+            let (x, y) = (self.last_mouse_xy.0 as f64, self.last_mouse_xy.1 as f64);
+
+            self.paddle_target = (x, y);
+            self.paddle =
+                Some(
+                    self.fluid
+                        .add_obstacle(Obstacle::new(x, y, 0.0, Shape::Circle { radius })),
+                );
+        }
+    }
+
+    /// Removes every obstacle, the paddle included.
+    pub fn clear_obstacles(&mut self) {
+        self.fluid.clear_obstacles();
+        self.paddle = None;
+    }
+
+    pub fn set_pointer_mode(&mut self, mode: PointerMode) {
+        self.pointer_mode = mode;
+    }
+
+    /// Paints or erases obstacles with the brush radius along the pointer's
+    /// path since `previous`, or just under it.
+    fn paint(&mut self, e: &web_sys::PointerEvent, previous: Option<(i32, i32)>) {
+        let to =
+            self.fluid
+                .get_grid_indices_from_xy(e.client_x() as f64, e.client_y() as f64, None);
+        let from = previous.map_or(to, |(x, y)| {
+            self.fluid
+                .get_grid_indices_from_xy(x as f64, y as f64, None)
+        });
+
+        let solid = self.pointer_mode == PointerMode::Paint && !e.shift_key();
+
+        self.fluid
+            .paint_obstacle(from, to, self.mouse_radius, solid);
+    }
+
+    /// Makes every element matching one of `selectors` an obstacle, kept in
+    /// place as the page scrolls and resizes.
+    pub fn set_dom_obstacles(&mut self, selectors: Vec<String>) {
+        self.dom_selectors = selectors;
+        self.refresh_dom_obstacles();
+    }
+
+    /// Replaces the obstacles of the DOM elements with their current bounding
+    /// rects, leaving out those outside the canvas.
+    pub fn refresh_dom_obstacles(&mut self) {
+        self.dom_dirty = false;
+
+        if self.dom_selectors.is_empty() && self.dom_obstacles.is_empty() {
+            return;
+        }
+
+        let document = web_sys::window().unwrap().document().unwrap();
+        let width = self.canvas.width() as f64;
+        let height = self.canvas.height() as f64;
+
+        let mut obstacles = vec![];
+
+        for selector in self.dom_selectors.iter() {
+            let Ok(elements) = document.query_selector_all(selector) else {
+                continue;
+            };
+
+            for j in 0..elements.length() {
+                let Some(element) = elements
+                    .item(j)
+                    .and_then(|node| node.dyn_into::<web_sys::Element>().ok())
+                else {
+                    continue;
+                };
+
+                let rect = element.get_bounding_client_rect();
+
+                if rect.width() <= 0.0
+                    || rect.height() <= 0.0
+                    || rect.right() < 0.0
+                    || rect.bottom() < 0.0
+                    || rect.left() > width
+                    || rect.top() > height
+                {
+                    continue;
+                }
+
+                obstacles.push(Obstacle::new(
+                    rect.left() + rect.width() / 2.0,
+                    rect.top() + rect.height() / 2.0,
+                    0.0,
+                    Shape::Rectangle {
+                        width: rect.width(),
+                        height: rect.height(),
+                    },
+                ));
+            }
+        }
+
+        let previous = std::mem::take(&mut self.dom_obstacles);
+
+        self.dom_obstacles = self.fluid.replace_obstacles(&previous, obstacles);
+    }
+
+    /// Gives the paddle the velocity that brings it to the pointer by the end
+    /// of the step, so the fluid is pushed as fast as the pointer moves.
+    fn move_paddle(&mut self) {
+        let Some(id) = self.paddle else {
+            return;
+        };
+
+        let Some(paddle) = self.fluid.obstacles.iter().find(|o| o.id == id) else {
+            return;
+        };
+
+        let velocity = (
+            (self.paddle_target.0 - paddle.x) / self.fluid.delta_t,
+            (self.paddle_target.1 - paddle.y) / self.fluid.delta_t,
+        );
+
+        self.fluid.set_obstacle_velocity(id, velocity, 0.0);
+    }
+
+    pub fn cycle_dye_hue(&mut self, degrees_per_second: f64) {
+        self.dye_hue_speed = degrees_per_second;
+        self.show_dye = true;
+    }
+
+    /// Fills the painted cells and those of the shape obstacles and the
+    /// paddle. The DOM obstacles are left out, as the page draws them itself.
+    pub fn draw_obstacles(&self, ctx: &web_sys::CanvasRenderingContext2d) {
+        let shapes = self
+            .fluid
+            .obstacles
+            .iter()
+            .filter(|obstacle| !self.dom_obstacles.contains(&obstacle.id));
+
+        ctx.set_fill_style_str("#555");
+
+        let fill_cell = |i: i32, k: i32| {
+            let (x, y) = self.fluid.get_xy_from_grid_indices(i, k, None);
+
+            ctx.fill_rect(
+                x,
+                y,
+                self.fluid.square_size + 1.0,
+                self.fluid.square_size + 1.0,
+            );
+        };
+
+        for obstacle in shapes {
+            for (i, k) in self.fluid.get_obstacle_cells(obstacle) {
+                fill_cell(i, k);
+            }
+        }
+
+        if self.fluid.has_painted {
+            let painted = self
+                .fluid
+                .painted
+                .interior()
+                .filter(|&(_, _, &painted)| painted != 0);
+
+            for (i, k, _) in painted {
+                fill_cell(i, k);
+            }
+        }
+    }
+
     pub fn draw_gridlines(&self, ctx: &web_sys::CanvasRenderingContext2d) {
         ctx.set_stroke_style_str("#555");
         ctx.set_line_width(1.0);
@@ -285,13 +664,11 @@ impl Scene {
         &self,
         ctx: &web_sys::CanvasRenderingContext2d,
         scale: f64,
-        x: usize,
-        y: usize,
+        x: i32,
+        y: i32,
         i: u8,
         k: u8,
     ) {
-        let x = x as i32;
-        let y = y as i32;
         let i = i as f64;
         let k = k as f64;
 
@@ -300,8 +677,8 @@ impl Scene {
         let x = xx + (i + 0.5) * scale;
         let y = yy + (k + 0.5) * scale;
 
-        let v = self.fluid.interpolate(x, y, Field::V) * self.fluid.delta_t;
-        let u = self.fluid.interpolate(x, y, Field::U) * self.fluid.delta_t;
+        let v = self.sample(x, y, Field::V) * self.fluid.delta_t;
+        let u = self.sample(x, y, Field::U) * self.fluid.delta_t;
 
         ctx.set_stroke_style_str("#08f");
         ctx.set_fill_style_str("#08f");
@@ -374,12 +751,9 @@ impl Scene {
         let s0 = Rc::clone(&self_ref);
         let s1 = Rc::clone(&self_ref);
         let s2 = Rc::clone(&self_ref);
+        let s3 = Rc::clone(&self_ref);
 
-        s0.borrow_mut()
-            .as_mut()
-            .unwrap()
-            .fluid
-            .fill_edges_with_obstacles();
+        s0.borrow_mut().as_mut().unwrap().fluid.update_obstacles();
 
         s0.borrow_mut()
             .as_mut()
@@ -413,8 +787,21 @@ impl Scene {
 
                     let norm = f64::sqrt((delta_x * delta_x + delta_y * delta_y) as f64);
 
+                    let previous = s.last_mouse_xy;
                     s.last_mouse_xy = (e.client_x(), e.client_y());
 
+                    if s.paddle.is_some() {
+                        s.paddle_target = (e.client_x() as f64, e.client_y() as f64);
+                        return;
+                    }
+
+                    if s.pointer_mode != PointerMode::Stir {
+                        if s.is_mouse_down {
+                            s.paint(&e, Some(previous));
+                        }
+                        return;
+                    }
+
                     let (x, y) = fluid.get_grid_indices_from_xy(
                         e.client_x() as f64,
                         e.client_y() as f64,
@@ -425,32 +812,34 @@ impl Scene {
                         return;
                     }
 
-                    for i in 0..(s.mouse_radius * 2 + 1) {
-                        for k in 0..(s.mouse_radius * 2 + 1) {
-                            let xx = x - s.mouse_radius + i;
-                            let yy = y - s.mouse_radius + k;
-
-                            if fluid.b.get(xx, yy) == 0
-                                || fluid.b.get(xx - 1, yy) == 0
-                                || fluid.b.get(xx, yy - 1) == 0
-                            {
-                                continue;
-                            }
-
-                            let mult = gaussian(
-                                i - s.mouse_radius,
-                                k - s.mouse_radius,
-                                s.mouse_radius as f64 / 2.0,
-                            ) * 2.0
-                                * 1000.0
-                                / delta_t;
-
-                            fluid.u.update(xx, yy, |u| u + mult * delta_x as f64);
-                            fluid.v.update(xx, yy, |v| v + mult * delta_y as f64);
-                            fluid.s.update(xx, yy, |sm| {
-                                f64::min(sm + mult * norm * 3.0, s.max_velocity * 3.0)
-                            });
+                    let radius = s.mouse_radius;
+
+                    for (xx, yy) in fluid
+                        .b
+                        .region((x - radius, y - radius), (x + radius, y + radius))
+                    {
+                        if fluid.b.get(xx, yy) == 0
+                            || fluid.b.get(xx - 1, yy) == 0
+                            || fluid.b.get(xx, yy - 1) == 0
+                        {
+                            continue;
                         }
+
+                        let mult =
+                            gaussian(xx - x, yy - y, radius as f64 / 2.0) * 2.0 * 1000.0 / delta_t;
+
+                        fluid.u.update(xx, yy, |u| u + mult * delta_x as f64);
+                        fluid.v.update(xx, yy, |v| v + mult * delta_y as f64);
+                        fluid.add_smoke(
+                            xx,
+                            yy,
+                            mult * norm * 3.0,
+                            s.max_velocity * 3.0,
+                            s.dye_color,
+                        );
+                        fluid
+                            .t
+                            .update(xx, yy, |t| f64::min(t + mult * norm, s.max_velocity));
                     }
                 }
 
@@ -479,7 +868,9 @@ impl Scene {
 
                 if !s.is_mouse_down {
                     s.last_time = -1.0;
-                } else {
+                } else if s.pointer_mode != PointerMode::Stir {
+                    s.paint(&e, None);
+                } else if s.paddle.is_none() {
                     let fluid = &mut s.fluid;
 
                     let (x, y) = fluid.get_grid_indices_from_xy(
@@ -488,28 +879,29 @@ impl Scene {
                         None,
                     );
 
-                    for i in 0..(s.mouse_radius * 2 + 1) {
-                        for k in 0..(s.mouse_radius * 2 + 1) {
-                            let xx = x - s.mouse_radius + i;
-                            let yy = y - s.mouse_radius + k;
-
-                            if fluid.b.get(xx, yy) == 0
-                                || fluid.b.get(xx - 1, yy) == 0
-                                || fluid.b.get(xx, yy - 1) == 0
-                            {
-                                continue;
-                            }
-
-                            let mult = gaussian(
-                                i - s.mouse_radius,
-                                k - s.mouse_radius,
-                                s.mouse_radius as f64 / 2.0,
-                            );
-
-                            fluid
-                                .s
-                                .update(xx, yy, |sm| sm + mult * s.max_velocity * 1.5);
+                    let radius = s.mouse_radius;
+
+                    for (xx, yy) in fluid
+                        .b
+                        .region((x - radius, y - radius), (x + radius, y + radius))
+                    {
+                        if fluid.b.get(xx, yy) == 0
+                            || fluid.b.get(xx - 1, yy) == 0
+                            || fluid.b.get(xx, yy - 1) == 0
+                        {
+                            continue;
                         }
+
+                        let mult = gaussian(xx - x, yy - y, radius as f64 / 2.0);
+
+                        fluid.add_smoke(
+                            xx,
+                            yy,
+                            mult * s.max_velocity * 1.5,
+                            f64::INFINITY,
+                            s.dye_color,
+                        );
+                        fluid.t.update(xx, yy, |t| t + mult * s.max_velocity * 0.5);
                     }
                 }
             }
@@ -550,6 +942,7 @@ impl Scene {
                 s.canvas.set_height(height as u32);
 
                 s.fluid.resize(width, height);
+                s.dom_dirty = true;
             }
         }) as Box<dyn FnMut(_)>));
 
@@ -558,6 +951,21 @@ impl Scene {
         web_sys::window()
             .unwrap()
             .set_onresize(Some((*resize_cb).as_ref().unchecked_ref()));
+
+        let scroll_cb = Rc::new(Closure::wrap(Box::new(move |_e: web_sys::Event| {
+            if let Ok(s) = s3.try_borrow_mut().as_mut() {
+                let s = s.as_mut().unwrap();
+
+                // scroll events come much faster than frames
+                s.dom_dirty = true;
+            }
+        }) as Box<dyn FnMut(_)>));
+
+        s.scroll_cb.replace(Rc::clone(&scroll_cb));
+
+        web_sys::window()
+            .unwrap()
+            .set_onscroll(Some((*scroll_cb).as_ref().unchecked_ref()));
     }
 
     pub fn play(self_ref: Rc<RefCell<Option<Self>>>) {
@@ -573,10 +981,38 @@ impl Scene {
                     return;
                 }
 
-                let delta = now - s.then;
-                if delta > s.fluid.delta_t * 1000.0 {
-                    s.then = now;
-                    s.draw_next_frame();
+                // after a stall (hidden tab, paused), only catch up a little
+                // instead of fast forwarding through all the missed time
+                let delta = f64::min(now - s.then, Self::MAX_FRAME_TIME);
+                let step = s.fluid.delta_t * 1000.0;
+
+                s.then = now;
+                s.accumulator += delta;
+
+                if s.dom_dirty {
+                    s.refresh_dom_obstacles();
+                }
+
+                let start = web_sys::window().unwrap().performance().unwrap().now();
+                let mut steps = 0;
+
+                while s.accumulator >= step && steps < Self::MAX_STEPS_PER_FRAME {
+                    s.simulate();
+                    s.accumulator -= step;
+                    steps += 1;
+                }
+
+                // a device too slow to keep up drops the time it could not simulate
+                s.accumulator = s.accumulator.min(step);
+
+                // the leftover time is drawn as a blend of the last two steps,
+                // so motion stays smooth on displays faster than the step rate
+                s.blend = (s.accumulator / step).clamp(0.0, 1.0);
+                s.render();
+
+                if steps > 0 {
+                    let elapsed = web_sys::window().unwrap().performance().unwrap().now() - start;
+                    s.time_to_next_frame_ring.push(elapsed / steps as f64);
                 }
 
                 let id = web_sys::window()
@@ -624,15 +1060,21 @@ impl Scene {
         drop(s.mouse_move_cb.take());
         drop(s.mouse_down_cb.take());
         drop(s.resize_cb.take());
+        drop(s.scroll_cb.take());
 
         window.set_onpointermove(None);
         window.set_onpointerdown(None);
         window.set_onpointerup(None);
         window.set_onresize(None);
+        window.set_onscroll(None);
     }
 
     pub fn toggle_playing(self_ref: Rc<RefCell<Option<Self>>>) {
         let enable_playing = self_ref.borrow().as_ref().unwrap().enable_playing;
         self_ref.borrow_mut().as_mut().unwrap().enable_playing = !enable_playing;
     }
+
+    pub fn toggle_show_pressure(&mut self) {
+        self.show_pressure = !self.show_pressure;
+    }
 }
@@ -6,6 +6,21 @@ pub fn map(x: f64, in_min: f64, in_max: f64, out_min: f64, out_max: f64) -> f64
     (x - in_min) * (out_max - out_min) / (in_max - in_min) + out_min
 }
 
+/// Converts a hue in degrees and saturation/lightness in percent to rgb
+/// channels between 0 and 1.
+pub fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> [f64; 3] {
+    let s = saturation / 100.0;
+    let l = lightness / 100.0;
+
+    let a = s * f64::min(l, 1.0 - l);
+    let f = |n: f64| {
+        let k = (n + hue / 30.0) % 12.0;
+        l - a * f64::min(k - 3.0, 9.0 - k).clamp(-1.0, 1.0)
+    };
+
+    [f(0.0), f(8.0), f(4.0)]
+}
+
 pub fn is_mobile() -> bool {
     let user_agent = web_sys::window()
         .unwrap()